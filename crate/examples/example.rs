@@ -19,7 +19,7 @@ fn main() {
         let start = PreciseTime::now();
 
         // Apply the effect in the HSV colour space
-        photon::colour_spaces::hsl(&mut img, effects[i], 0.2);
+        photon::colour_spaces::hsl(&mut img, effects[i], 0.2).unwrap();
 
         // Write the contents of this image in JPG format.
         photon::native::save_image(img, &("examples/example_output/".to_owned() + &effects[i].to_owned() + ".JPG"));
@@ -6,9 +6,10 @@ extern crate wasm_bindgen;
 use crate::helpers;
 use crate::{PhotonImage, Rgb};
 extern crate palette;
-use palette::{Pixel, Lch, Shade, Saturate, Srgba, Srgb, Lab};
+use palette::{Srgb, Lab};
 use wasm_bindgen::prelude::*;
-use crate::channels::palette::Hue;
+use serde::{Serialize, Deserialize};
+use crate::colour_spaces::{adjust_colour, ColourAdjustment, ColourSpace};
 
 /// Alter a select channel by incrementing or decrementing its value by a constant.
 /// 
@@ -176,7 +177,7 @@ pub fn alter_channels(img: &mut PhotonImage, r_amt: i16, g_amt: i16, b_amt: i16)
     if b_amt > 255 {
         panic!("Invalid b_amt passed. Amount to inc/dec channel by should be between -255 and 255");
     }
-    let end = img.raw_pixels.len() - 4;
+    let end = img.raw_pixels.len();
     
     for i in (0..end).step_by(4) {
         let r_val: i16 = img.raw_pixels[i] as i16 + r_amt as i16;
@@ -309,51 +310,98 @@ pub fn swap_channels(img: &mut PhotonImage, mut channel1: usize, mut channel2: u
     };
 }
 
+/// A selection of the Red, Green and Blue channels, for effects that can target any subset of them.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ChannelMask {
+    red: bool,
+    green: bool,
+    blue: bool,
+}
+
+#[wasm_bindgen]
+impl ChannelMask {
+    /// Create a channel mask.
+    #[wasm_bindgen(constructor)]
+    pub fn new(red: bool, green: bool, blue: bool) -> ChannelMask {
+        ChannelMask { red, green, blue }
+    }
+
+    /// Select all three channels.
+    pub fn all() -> ChannelMask {
+        ChannelMask::new(true, true, true)
+    }
+
+    /// Get whether the Red channel is selected.
+    pub fn get_red(&self) -> bool {
+        self.red
+    }
+
+    /// Get whether the Green channel is selected.
+    pub fn get_green(&self) -> bool {
+        self.green
+    }
+
+    /// Get whether the Blue channel is selected.
+    pub fn get_blue(&self) -> bool {
+        self.blue
+    }
+}
+
+impl ChannelMask {
+    /// Get whether a channel is selected, given its index (0 = Red, 1 = Green, 2 = Blue).
+    pub fn contains(&self, channel: usize) -> bool {
+        match channel {
+            0 => self.red,
+            1 => self.green,
+            2 => self.blue,
+            _ => false,
+        }
+    }
+}
+
 /// Selective hue rotation.
 /// 
-/// Only rotate the hue of a pixel if its RGB values are within a specified range.
-/// This function only rotates a pixel's hue to another  if it is visually similar to the colour specified.
+/// Only rotate the hue of a pixel if its colour is visually similar to the colour specified.
 /// For example, if a user wishes all pixels that are blue to be changed to red, they can selectively specify  only the blue pixels to be changed.
+/// Pixels whose distance to the reference colour lies between `tolerance` and `tolerance + falloff` are
+/// partially rotated, so that the edit blends smoothly into the rest of the image.
 /// # Arguments
 /// * `img` - A PhotonImage.
 /// * `ref_color` - The `RGB` value of the reference color (to be compared to)
 /// * `degrees` - The amount of degrees to hue rotate by.
+/// * `metric` - The colour difference formula used to compare colours. See `DeltaE`.
+/// * `tolerance` - Pixels within this colour difference of `ref_color` are fully affected. 40.0 is a reasonable default for CIE76.
+/// * `falloff` - Width of the soft edge beyond `tolerance`, in the same units. 0.0 gives a hard cut-off.
 /// 
 /// # Example
 ///
 /// ```
 /// // For example, to only rotate the pixels that are of RGB value RGB{20, 40, 60}:
 /// let ref_color = Rgb{20, 40, 60};
-/// photon::channels::selective_hue_rotate(&mut img, ref_color, 180);
+/// photon::channels::selective_hue_rotate(&mut img, ref_color, 180.0, DeltaE::Ciede2000, 15.0, 5.0);
 /// ```
 #[wasm_bindgen]
-pub fn selective_hue_rotate(mut photon_image: &mut PhotonImage, ref_color: Rgb, degrees: f32) {
+pub fn selective_hue_rotate(photon_image: &mut PhotonImage, ref_color: Rgb, degrees: f32, metric: DeltaE, tolerance: f32, falloff: f32) {
     let img = helpers::dyn_image_from_raw(&photon_image);
-    
     let (_width, _height) = img.dimensions();
     let mut img = img.to_rgba();
+
+    // Reference colour to compare the current pixel's colour to
+    let lab = lab_from_rgb(ref_color.r, ref_color.g, ref_color.b);
+
     for x in 0.._width {
         for y in 0.._height {
-            let px = img.get_pixel(x, y);
+            let px_data = img.get_pixel(x, y).data;
 
-            // Reference colour to compare the current pixel's colour to
-            let lab: Lab = Srgb::new(ref_color.r as f32 / 255.0, ref_color.g as f32 / 255.0, ref_color.b as f32 / 255.0).into();
-      
             // Convert the current pixel's colour to the l*a*b colour space
-            let r_val: f32 = px.data[0] as f32 / 255.0;
-            let g_val: f32 = px.data[1] as f32 / 255.0;
-            let b_val: f32 = px.data[2] as f32 / 255.0;
-
-            let px_lab: Lab = Srgb::new(r_val, g_val, b_val).into();
+            let px_lab = lab_from_rgb(px_data[0], px_data[1], px_data[2]);
 
-            let sim = color_sim(lab, px_lab);
-            if sim > 0 && sim < 40 {
-                let px_data = img.get_pixel(x, y).data;
-                let color = Srgba::from_raw(&px_data).into_format();
-
-                let hue_rotated_color = Lch::from(color).shift_hue(degrees);
+            let weight = falloff_weight(delta_e(lab, px_lab, metric), tolerance, falloff);
+            if weight > 0.0 {
+                let new_data = adjust_colour(px_data, ColourSpace::Lch, ColourAdjustment::ShiftHue, degrees);
                 img.put_pixel(x, y, image::Rgba {
-                    data: Srgba::from_linear(hue_rotated_color.into()).into_format().into_raw()
+                    data: mix_pixels(px_data, new_data, weight)
                 });
             }
         }
@@ -393,6 +441,21 @@ pub fn invert(mut photon_image: &mut PhotonImage) {
     photon_image.raw_pixels = img.to_vec();
 }
 
+/// Colour difference formulas, used to decide how similar two colours are.
+///
+/// * **Cie76** - Euclidean distance in the L\*a\*b\* colour space. Fast, but overstates differences in saturated colours.
+/// * **Cie94** - Weights chroma and hue differences by the chroma of the reference colour (graphic arts constants).
+/// * **Ciede2000** - The current CIE recommendation, which corrects for the perceptual non-uniformity of blues and neutrals.
+///
+/// All three produce values on roughly the same scale, where 1.0 is about the smallest difference the eye can notice.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeltaE {
+    Cie76 = 1,
+    Cie94 = 2,
+    Ciede2000 = 3,
+}
+
 /// Get the similarity of two colours in the l*a*b colour space using the CIE76 formula.
 pub fn color_sim(lab1: Lab, lab2: Lab) -> i64 {
     let l_comp = lab2.l - lab1.l;
@@ -409,6 +472,168 @@ pub fn color_sim(lab1: Lab, lab2: Lab) -> i64 {
     return sq_rt;
 }
 
+/// Get the difference between two colours in the l*a*b colour space, using the given formula.
+///
+/// `lab1` is treated as the reference colour, which matters for the asymmetric CIE94 formula.
+///
+/// # Arguments
+/// * `lab1` - The reference colour.
+/// * `lab2` - The colour to compare against the reference.
+/// * `metric` - The colour difference formula to use.
+///
+/// # Example
+///
+/// ```
+/// let diff = photon::channels::delta_e(lab1, lab2, DeltaE::Ciede2000);
+/// ```
+pub fn delta_e(lab1: Lab, lab2: Lab, metric: DeltaE) -> f32 {
+    let (l1, a1, b1) = (lab1.l as f64, lab1.a as f64, lab1.b as f64);
+    let (l2, a2, b2) = (lab2.l as f64, lab2.a as f64, lab2.b as f64);
+
+    let diff = match metric {
+        DeltaE::Cie76 => ((l2 - l1).powi(2) + (a2 - a1).powi(2) + (b2 - b1).powi(2)).sqrt(),
+        DeltaE::Cie94 => cie94(l1, a1, b1, l2, a2, b2),
+        DeltaE::Ciede2000 => ciede2000(l1, a1, b1, l2, a2, b2),
+    };
+    diff as f32
+}
+
+fn cie94(l1: f64, a1: f64, b1: f64, l2: f64, a2: f64, b2: f64) -> f64 {
+    // Graphic arts weighting constants
+    let (k_l, k1, k2) = (1.0, 0.045, 0.015);
+
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+
+    let delta_l = l1 - l2;
+    let delta_c = c1 - c2;
+    let delta_a = a1 - a2;
+    let delta_b = b1 - b2;
+
+    // Rounding can push the squared hue difference slightly below zero for near-identical colours
+    let delta_h_sq = (delta_a * delta_a + delta_b * delta_b - delta_c * delta_c).max(0.0);
+
+    let s_c = 1.0 + k1 * c1;
+    let s_h = 1.0 + k2 * c1;
+
+    ((delta_l / k_l).powi(2) + (delta_c / s_c).powi(2) + delta_h_sq / (s_h * s_h)).sqrt()
+}
+
+fn ciede2000(l1: f64, a1: f64, b1: f64, l2: f64, a2: f64, b2: f64) -> f64 {
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+    let c_bar = (c1 + c2) / 2.0;
+
+    let c_bar7 = c_bar.powi(7);
+    let g = 0.5 * (1.0 - (c_bar7 / (c_bar7 + 25f64.powi(7))).sqrt());
+
+    let a1_prime = (1.0 + g) * a1;
+    let a2_prime = (1.0 + g) * a2;
+
+    let c1_prime = (a1_prime * a1_prime + b1 * b1).sqrt();
+    let c2_prime = (a2_prime * a2_prime + b2 * b2).sqrt();
+
+    let hue_angle = |b: f64, a: f64| {
+        if b == 0.0 && a == 0.0 {
+            0.0
+        }
+        else {
+            let h = b.atan2(a).to_degrees();
+            if h < 0.0 { h + 360.0 } else { h }
+        }
+    };
+    let h1_prime = hue_angle(b1, a1_prime);
+    let h2_prime = hue_angle(b2, a2_prime);
+
+    let delta_l_prime = l2 - l1;
+    let delta_c_prime = c2_prime - c1_prime;
+
+    let chroma_product = c1_prime * c2_prime;
+    let delta_h_prime = if chroma_product == 0.0 {
+        0.0
+    }
+    else if (h2_prime - h1_prime).abs() <= 180.0 {
+        h2_prime - h1_prime
+    }
+    else if h2_prime - h1_prime > 180.0 {
+        h2_prime - h1_prime - 360.0
+    }
+    else {
+        h2_prime - h1_prime + 360.0
+    };
+    let delta_big_h_prime = 2.0 * chroma_product.sqrt() * (delta_h_prime / 2.0).to_radians().sin();
+
+    let l_bar_prime = (l1 + l2) / 2.0;
+    let c_bar_prime = (c1_prime + c2_prime) / 2.0;
+
+    let h_bar_prime = if chroma_product == 0.0 {
+        h1_prime + h2_prime
+    }
+    else if (h1_prime - h2_prime).abs() <= 180.0 {
+        (h1_prime + h2_prime) / 2.0
+    }
+    else if h1_prime + h2_prime < 360.0 {
+        (h1_prime + h2_prime + 360.0) / 2.0
+    }
+    else {
+        (h1_prime + h2_prime - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * (h_bar_prime - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_bar_prime).to_radians().cos()
+        + 0.32 * (3.0 * h_bar_prime + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_bar_prime - 63.0).to_radians().cos();
+
+    let delta_theta = 30.0 * (-((h_bar_prime - 275.0) / 25.0).powi(2)).exp();
+
+    let c_bar_prime7 = c_bar_prime.powi(7);
+    let r_c = 2.0 * (c_bar_prime7 / (c_bar_prime7 + 25f64.powi(7))).sqrt();
+
+    let l_offset = (l_bar_prime - 50.0).powi(2);
+    let s_l = 1.0 + (0.015 * l_offset) / (20.0 + l_offset).sqrt();
+    let s_c = 1.0 + 0.045 * c_bar_prime;
+    let s_h = 1.0 + 0.015 * c_bar_prime * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    let l_term = delta_l_prime / s_l;
+    let c_term = delta_c_prime / s_c;
+    let h_term = delta_big_h_prime / s_h;
+
+    (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt()
+}
+
+/// Get how strongly a pixel should be affected by a selective edit, from 0 (untouched) to 1 (fully affected).
+///
+/// Colours within `tolerance` of the reference are fully affected, and the weight then eases out to 0
+/// over the next `falloff` units, so that edits blend smoothly instead of cutting off.
+///
+/// # Arguments
+/// * `distance` - The colour difference between the pixel and the reference colour, see `delta_e`.
+/// * `tolerance` - The distance up to which pixels are fully affected.
+/// * `falloff` - The width of the soft edge beyond `tolerance`. A value of 0.0 gives a hard cut-off.
+pub fn falloff_weight(distance: f32, tolerance: f32, falloff: f32) -> f32 {
+    if distance <= tolerance {
+        return 1.0;
+    }
+    if falloff <= 0.0 || distance >= tolerance + falloff {
+        return 0.0;
+    }
+    // Smoothstep from 1 at the tolerance down to 0 at the outer edge of the falloff
+    let t = 1.0 - (distance - tolerance) / falloff;
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Convert an sRGB colour to the l*a*b colour space.
+pub fn lab_from_rgb(r: u8, g: u8, b: u8) -> Lab {
+    Srgb::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0).into()
+}
+
+/// Linearly mix two RGBA pixels, keeping the first pixel's alpha.
+/// A `weight` of 0 returns `original`, and a `weight` of 1 returns `edited`'s colour.
+pub fn mix_pixels(original: [u8; 4], edited: [u8; 4], weight: f32) -> [u8; 4] {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * weight).round() as u8;
+    [mix(original[0], edited[0]), mix(original[1], edited[1]), mix(original[2], edited[2]), original[3]]
+}
 
 /// Selectively lighten an image.
 /// 
@@ -418,100 +643,94 @@ pub fn color_sim(lab1: Lab, lab2: Lab) -> i64 {
 /// * `img` - A PhotonImage.
 /// * `ref_color` - The `RGB` value of the reference color (to be compared to)
 /// * `amt` - The level from 0 to 1 to lighten the hue by. Increasing by 10% would have an `amt` of 0.1
+/// * `metric` - The colour difference formula used to compare colours. See `DeltaE`.
+/// * `tolerance` - Pixels within this colour difference of `ref_color` are fully lightened.
+/// * `falloff` - Width of the soft edge beyond `tolerance`. 0.0 gives a hard cut-off.
 /// 
 /// # Example
 ///
 /// ```
 /// // For example, to only lighten the pixels that are of or similar to RGB value RGB{20, 40, 60}:
 /// let ref_color = Rgb{20, 40, 60};
-/// photon::channels::selective_lighten(&mut img, ref_color, 0.2);
+/// photon::channels::selective_lighten(&mut img, ref_color, 0.2, DeltaE::Cie76, 40.0, 10.0);
 /// ```
 #[wasm_bindgen]
-pub fn selective_lighten(img: &mut PhotonImage, ref_color: Rgb, amt: f32) {
-    return selective(img, "lighten", ref_color, amt);
+pub fn selective_lighten(img: &mut PhotonImage, ref_color: Rgb, amt: f32, metric: DeltaE, tolerance: f32, falloff: f32) {
+    return selective(img, ColourAdjustment::Lighten, ref_color, amt, metric, tolerance, falloff);
 }
 
 /// Selectively desaturate pixel colours which are similar to the reference colour provided.
 /// 
-/// Similarity between two colours is calculated via the CIE76 formula.
-/// Only desaturates the hue of a pixel if its similarity to the reference colour is within the range in the algorithm.
+/// Similarity between two colours is calculated with the chosen `DeltaE` formula.
+/// Only desaturates the hue of a pixel if its difference from the reference colour is within the tolerance,
+/// fading the effect out over the falloff range.
 /// For example, if a user wishes all pixels that are blue to be desaturated by 0.1, they can selectively specify  only the blue pixels to be changed.
 /// # Arguments
 /// * `img` - A PhotonImage.
 /// * `ref_color` - The `RGB` value of the reference color (to be compared to)
 /// * `amt` - The amount of desaturate the colour by. 
+/// * `metric` - The colour difference formula used to compare colours. See `DeltaE`.
+/// * `tolerance` - Pixels within this colour difference of `ref_color` are fully desaturated.
+/// * `falloff` - Width of the soft edge beyond `tolerance`. 0.0 gives a hard cut-off.
 /// 
 /// # Example
 ///
 /// ```
 /// // For example, to only desaturate the pixels that are similar to the RGB value RGB{20, 40, 60}:
 /// let ref_color = Rgb{20, 40, 60};
-/// photon::channels::selective_desaturate(&mut img, ref_color, 0.1);
+/// photon::channels::selective_desaturate(&mut img, ref_color, 0.1, DeltaE::Cie94, 20.0, 10.0);
 /// ```
 #[wasm_bindgen]
-pub fn selective_desaturate(img: &mut PhotonImage, ref_color: Rgb, amt: f32) {
-    return selective(img, "desaturate", ref_color, amt);
+pub fn selective_desaturate(img: &mut PhotonImage, ref_color: Rgb, amt: f32, metric: DeltaE, tolerance: f32, falloff: f32) {
+    return selective(img, ColourAdjustment::Desaturate, ref_color, amt, metric, tolerance, falloff);
 }
 
 /// Selectively saturate pixel colours which are similar to the reference colour provided.
 /// 
-/// Similarity between two colours is calculated via the CIE76 formula.
-/// Only saturates the hue of a pixel if its similarity to the reference colour is within the range in the algorithm.
+/// Similarity between two colours is calculated with the chosen `DeltaE` formula.
+/// Only saturates the hue of a pixel if its difference from the reference colour is within the tolerance,
+/// fading the effect out over the falloff range.
 /// For example, if a user wishes all pixels that are blue to have an increase in saturation by 10%, they can selectively specify only the blue pixels to be changed.
 /// # Arguments
 /// * `img` - A PhotonImage.
 /// * `ref_color` - The `RGB` value of the reference color (to be compared to)
 /// * `amt` - The amount of saturate the colour by. 
+/// * `metric` - The colour difference formula used to compare colours. See `DeltaE`.
+/// * `tolerance` - Pixels within this colour difference of `ref_color` are fully saturated.
+/// * `falloff` - Width of the soft edge beyond `tolerance`. 0.0 gives a hard cut-off.
 /// 
 /// # Example
 ///
 /// ```
 /// // For example, to only increase the saturation of pixels that are similar to the RGB value RGB{20, 40, 60}:
 /// let ref_color = Rgb{20, 40, 60};
-/// photon::channels::selective_saturate(&mut img, ref_color, 0.1);
+/// photon::channels::selective_saturate(&mut img, ref_color, 0.1, DeltaE::Ciede2000, 15.0, 5.0);
 /// ```
 #[wasm_bindgen]
-pub fn selective_saturate(img: &mut PhotonImage, ref_color: Rgb, amt: f32) {
-    selective(img, "saturate", ref_color, amt);
+pub fn selective_saturate(img: &mut PhotonImage, ref_color: Rgb, amt: f32, metric: DeltaE, tolerance: f32, falloff: f32) {
+    selective(img, ColourAdjustment::Saturate, ref_color, amt, metric, tolerance, falloff);
 }
 
-fn selective(mut photon_image: &mut PhotonImage, mode: &'static str, ref_color:Rgb, amt: f32) {
+fn selective(photon_image: &mut PhotonImage, adjustment: ColourAdjustment, ref_color: Rgb, amt: f32, metric: DeltaE, tolerance: f32, falloff: f32) {
     let img = helpers::dyn_image_from_raw(&photon_image);
     let (_width, _height) = img.dimensions();
     let mut img = img.to_rgba();
+
+    // Reference colour to compare the current pixel's colour to
+    let lab = lab_from_rgb(ref_color.r, ref_color.g, ref_color.b);
+
     for x in 0.._width {
         for y in 0.._height {
-            let px = img.get_pixel(x, y);
+            let px_data = img.get_pixel(x, y).data;
 
-            // Reference colour to compare the current pixel's colour to
-            let lab: Lab = Srgb::new(ref_color.r as f32 / 255.0, ref_color.g as f32 / 255.0, ref_color.b as f32 / 255.0).into();
-      
             // Convert the current pixel's colour to the l*a*b colour space
-            let r_val: f32 = px.data[0] as f32 / 255.0;
-            let g_val: f32 = px.data[1] as f32 / 255.0;
-            let b_val: f32 = px.data[2] as f32 / 255.0;
-
-            let px_lab: Lab = Srgb::new(r_val, g_val, b_val).into();
-
-            let sim = color_sim(lab, px_lab);
-            if sim > 0 && sim < 40 {
-                let px_data = img.get_pixel(x, y).data;
-                let lch_colour: Lch = Srgb::from_raw(&px_data)
-                    .into_format()
-                    .into_linear()
-                    .into();
-
-                let new_color = match mode {
-                // Match a single value
-                "desaturate" => lch_colour.desaturate(amt),
-                "saturate" => lch_colour.saturate(amt),
-                "lighten" => lch_colour.lighten(amt), 
-                "darken" => lch_colour.darken(amt),
-                _ => lch_colour.saturate(amt),
-                };
-            
+            let px_lab = lab_from_rgb(px_data[0], px_data[1], px_data[2]);
+
+            let weight = falloff_weight(delta_e(lab, px_lab, metric), tolerance, falloff);
+            if weight > 0.0 {
+                let new_data = adjust_colour(px_data, ColourSpace::Lch, adjustment, amt);
                 img.put_pixel(x, y, image::Rgba {
-                        data: Srgba::from_linear(new_color.into()).into_format().into_raw()
+                        data: mix_pixels(px_data, new_data, weight)
                 });
 
             }
@@ -521,49 +740,47 @@ fn selective(mut photon_image: &mut PhotonImage, mode: &'static str, ref_color:R
 }
 
 /// Selectively changes a pixel to greyscale if it is *not* visually similar or close to the colour specified.
-/// Only changes the colour of a pixel if its RGB values are within a specified range.
+/// Only keeps the colour of a pixel if its difference from the reference colour is within the tolerance.
 /// 
-/// (Similarity between two colours is calculated via the CIE76 formula.)
+/// (Similarity between two colours is calculated with the chosen `DeltaE` formula.)
 /// For example, if a user wishes all pixels that are *NOT* blue to be displayed in greyscale, they can selectively specify only the blue pixels to be
-/// kept in the photo.
+/// kept in the photo. Pixels within the falloff range are only partially greyscaled.
 /// # Arguments
 /// * `img` - A PhotonImage.
 /// * `ref_color` - The `RGB` value of the reference color (to be compared to) 
+/// * `metric` - The colour difference formula used to compare colours. See `DeltaE`.
+/// * `tolerance` - Pixels within this colour difference of `ref_color` keep their colour. 30.0 is a reasonable default for CIE76.
+/// * `falloff` - Width of the soft edge beyond `tolerance`. 0.0 gives a hard cut-off.
 /// 
 /// # Example
 ///
 /// ```
 /// // For example, to greyscale all pixels that are *not* visually similar to the RGB colour RGB{20, 40, 60}:
 /// let ref_color = Rgb{20, 40, 60};
-/// photon::channels::selective_greyscale(&mut img, ref_color);
+/// photon::channels::selective_greyscale(&mut img, ref_color, DeltaE::Cie76, 30.0, 10.0);
 /// ```
 #[wasm_bindgen]
-pub fn selective_greyscale(mut photon_image: PhotonImage, ref_color: Rgb) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
+pub fn selective_greyscale(photon_image: &mut PhotonImage, ref_color: Rgb, metric: DeltaE, tolerance: f32, falloff: f32) {
+    let mut img = helpers::dyn_image_from_raw(photon_image);
+
+    // Reference colour to compare the current pixel's colour to
+    let lab = lab_from_rgb(ref_color.r, ref_color.g, ref_color.b);
+
     for x in 0..photon_image.width {
         for y in 0..photon_image.height {
-            let mut px = img.get_pixel(x, y);
-
-            // Reference colour to compare the current pixel's colour to
-            let lab: Lab = Srgb::new(ref_color.r as f32 / 255.0, ref_color.g as f32 / 255.0, ref_color.b as f32 / 255.0).into();
+            let px = img.get_pixel(x, y);
 
             // Convert the current pixel's colour to the l*a*b colour space
-            let r_val: f32 = px.data[0] as f32 / 255.0;
-            let g_val: f32 = px.data[1] as f32 / 255.0;
-            let b_val: f32 = px.data[2] as f32 / 255.0;
-
-            let px_lab: Lab = Srgb::new(r_val, g_val, b_val).into();
-
-            let sim = color_sim(lab, px_lab);
-            if sim > 30 {
-                let avg = px.data[0] as f32 * 0.3 + px.data[1] as f32 * 0.59 + px.data[2] as f32 * 0.11;
-                px.data[0] = avg as u8;
-                px.data[1] = avg as u8;
-                px.data[2] = avg as u8;
+            let px_lab = lab_from_rgb(px.data[0], px.data[1], px.data[2]);
+
+            let weight = 1.0 - falloff_weight(delta_e(lab, px_lab, metric), tolerance, falloff);
+            if weight > 0.0 {
+                let avg = (px.data[0] as f32 * 0.3 + px.data[1] as f32 * 0.59 + px.data[2] as f32 * 0.11) as u8;
+                let grey = mix_pixels(px.data, [avg, avg, avg, px.data[3]], weight);
+                img.put_pixel(x, y, image::Rgba(grey));
             }
-            img.put_pixel(x, y, px);
         }
     }
     let raw_pixels = img.raw_pixels();
     photon_image.raw_pixels = raw_pixels;
-}
\ No newline at end of file
+}
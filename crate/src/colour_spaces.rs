@@ -1,10 +1,12 @@
-//! Image manipulation effects in HSL, LCh and HSV.
+//! Image manipulation effects in HSL, LCh, HSV, Oklab and Oklch.
 
 extern crate image;
 extern crate rand;
 use image::{GenericImageView};
-use palette::{Hsl, Lch, Shade, Pixel, Saturate, Srgba, Hue, Hsv};
+use palette::{Hsl, Hwb, Lab, Lch, LabHue, Mix, Shade, Pixel, Saturate, Srgb, LinSrgb, Hue, GetHue, Hsv};
 use crate::{PhotonImage, Rgb, helpers};
+use serde::{Serialize, Deserialize};
+use std::fmt;
 extern crate wasm_bindgen;
 use wasm_bindgen::prelude::*;
 
@@ -50,18 +52,390 @@ use wasm_bindgen::prelude::*;
 //     photon_image.raw_pixels = img.to_vec();
 // }
 
+/// Colour spaces in which colour adjustments can be made. See `adjust`.
+///
+/// * **Lch** - The cylindrical form of CIE L\*a\*b\*, with perceptual lightness, chroma and hue.
+/// * **Hsl** - Hue, saturation and lightness.
+/// * **Hsv** - Hue, saturation and value.
+/// * **Oklab** - A perceptually uniform space with better hue linearity than CIE L\*a\*b\*, especially for blues.
+/// * **Lab** - CIE L\*a\*b\*.
+/// * **Hwb** - Hue, whiteness and blackness.
+/// * **Oklch** - The cylindrical form of Oklab, with lightness, chroma and hue.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ColourSpace {
+    Lch = 1,
+    Hsl = 2,
+    Hsv = 3,
+    Oklab = 4,
+    Lab = 5,
+    Hwb = 6,
+    Oklch = 7,
+}
+
+/// Colour adjustments which can be made in any `ColourSpace`. See `adjust`.
+///
+/// * **Saturate** - Increase the saturation (or chroma) by a fraction of its current value.
+/// * **Desaturate** - Decrease the saturation (or chroma) by a fraction of its current value.
+/// * **Lighten** - Increase the lightness by an amount from 0 to 1.
+/// * **Darken** - Decrease the lightness by an amount from 0 to 1.
+/// * **ShiftHue** - Rotate the hue by a number of degrees.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ColourAdjustment {
+    Saturate = 1,
+    Desaturate = 2,
+    Lighten = 3,
+    Darken = 4,
+    ShiftHue = 5,
+}
+
+impl ColourAdjustment {
+    /// Get the adjustment matching one of the mode names used by `lch`, `hsl` and `hsv`:
+    /// `saturate`, `desaturate`, `lighten`, `darken` or `shift_hue`.
+    pub fn from_mode(mode: &str) -> Option<ColourAdjustment> {
+        match mode {
+            "saturate" => Some(ColourAdjustment::Saturate),
+            "desaturate" => Some(ColourAdjustment::Desaturate),
+            "lighten" => Some(ColourAdjustment::Lighten),
+            "darken" => Some(ColourAdjustment::Darken),
+            "shift_hue" => Some(ColourAdjustment::ShiftHue),
+            _ => None,
+        }
+    }
+}
+
+/// Errors from the mode-string functions `lch`, `hsl` and `hsv`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColourSpaceError {
+    /// The mode isn't one of `saturate`, `desaturate`, `lighten`, `darken` or `shift_hue`.
+    UnknownMode(String),
+}
+
+impl fmt::Display for ColourSpaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColourSpaceError::UnknownMode(mode) => write!(f, "Unknown mode '{}'. Mode must be one of: saturate, desaturate, shift_hue, darken, lighten", mode),
+        }
+    }
+}
+
+impl std::error::Error for ColourSpaceError {}
+
+impl From<ColourSpaceError> for JsValue {
+    fn from(error: ColourSpaceError) -> JsValue {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+/// A colour in the Oklab colour space.
+///
+/// Oklab is a perceptually uniform colour space designed by Björn Ottosson, which predicts lightness,
+/// chroma and hue more evenly than CIE L\*a\*b\*. `l` ranges from 0 to 1, and `a` and `b` lie roughly
+/// between -0.4 and 0.4 for colours within the sRGB gamut.
+/// See <https://bottosson.github.io/posts/oklab/> for details.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl Oklab {
+    /// Create a new Oklab colour.
+    pub fn new(l: f32, a: f32, b: f32) -> Oklab {
+        Oklab { l, a, b }
+    }
+}
+
+impl From<LinSrgb> for Oklab {
+    fn from(rgb: LinSrgb) -> Self {
+        let l = 0.412_221_46 * rgb.red + 0.536_332_55 * rgb.green + 0.051_445_995 * rgb.blue;
+        let m = 0.211_903_5 * rgb.red + 0.680_699_5 * rgb.green + 0.107_396_96 * rgb.blue;
+        let s = 0.088_302_46 * rgb.red + 0.281_718_85 * rgb.green + 0.629_978_7 * rgb.blue;
+
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+        Oklab {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+}
+
+impl From<Oklab> for LinSrgb {
+    fn from(colour: Oklab) -> Self {
+        let l = colour.l + 0.396_337_78 * colour.a + 0.215_803_76 * colour.b;
+        let m = colour.l - 0.105_561_346 * colour.a - 0.063_854_17 * colour.b;
+        let s = colour.l - 0.089_484_18 * colour.a - 1.291_485_5 * colour.b;
+
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+
+        LinSrgb::new(
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        )
+    }
+}
+
+/// A colour in the Oklch colour space, the cylindrical form of `Oklab`.
+///
+/// Like LCh, Oklch describes a colour by its lightness, chroma and hue, but hue rotations and gradients
+/// stay perceptually even, without the shift towards purple that blues undergo in CIE LCh.
+/// `l` ranges from 0 to 1, and `chroma` lies roughly between 0 and 0.4 for colours within the sRGB gamut.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklch {
+    pub l: f32,
+    pub chroma: f32,
+    pub hue: LabHue,
+}
+
+impl Oklch {
+    /// Create a new Oklch colour, with the hue given in degrees.
+    pub fn new(l: f32, chroma: f32, hue: f32) -> Oklch {
+        Oklch { l, chroma, hue: LabHue::from_degrees(hue) }
+    }
+}
+
+impl From<Oklab> for Oklch {
+    fn from(colour: Oklab) -> Self {
+        Oklch {
+            l: colour.l,
+            chroma: (colour.a * colour.a + colour.b * colour.b).sqrt(),
+            hue: LabHue::from_radians(colour.b.atan2(colour.a)),
+        }
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(colour: Oklch) -> Self {
+        let (sin, cos) = colour.hue.to_radians().sin_cos();
+        Oklab {
+            l: colour.l,
+            a: colour.chroma * cos,
+            b: colour.chroma * sin,
+        }
+    }
+}
+
+impl From<LinSrgb> for Oklch {
+    fn from(rgb: LinSrgb) -> Self {
+        Oklab::from(rgb).into()
+    }
+}
+
+impl From<Oklch> for LinSrgb {
+    fn from(colour: Oklch) -> Self {
+        Oklab::from(colour).into()
+    }
+}
+
+impl Mix for Oklch {
+    type Scalar = f32;
+
+    fn mix(&self, other: &Oklch, factor: f32) -> Oklch {
+        let factor = num::clamp(factor, 0.0, 1.0);
+
+        // Greys have no meaningful hue, so take the hue of the other colour to avoid sweeping through unrelated hues
+        let (start_hue, end_hue) = if self.chroma < 1e-4 {
+            (other.hue, other.hue)
+        }
+        else if other.chroma < 1e-4 {
+            (self.hue, self.hue)
+        }
+        else {
+            (self.hue, other.hue)
+        };
+
+        // Interpolate the hue along the shortest path around the colour wheel
+        let hue_diff: f32 = (end_hue - start_hue).to_degrees();
+
+        Oklch {
+            l: self.l + factor * (other.l - self.l),
+            chroma: self.chroma + factor * (other.chroma - self.chroma),
+            hue: start_hue + factor * hue_diff,
+        }
+    }
+}
+
+impl Shade for Oklch {
+    type Scalar = f32;
+
+    fn lighten(&self, amount: f32) -> Oklch {
+        Oklch { l: self.l + amount, ..*self }
+    }
+}
+
+impl Saturate for Oklch {
+    type Scalar = f32;
+
+    fn saturate(&self, factor: f32) -> Oklch {
+        Oklch { chroma: self.chroma * (1.0 + factor), ..*self }
+    }
+}
+
+impl GetHue for Oklch {
+    type Hue = LabHue;
+
+    fn get_hue(&self) -> Option<LabHue> {
+        if self.chroma <= 0.0 {
+            None
+        }
+        else {
+            Some(self.hue)
+        }
+    }
+}
+
+impl Hue for Oklch {
+    fn with_hue<H: Into<LabHue>>(&self, hue: H) -> Oklch {
+        Oklch { hue: hue.into(), ..*self }
+    }
+
+    fn shift_hue<H: Into<LabHue>>(&self, amount: H) -> Oklch {
+        Oklch { hue: self.hue + amount.into(), ..*self }
+    }
+}
+
+/// Apply an adjustment to a colour type which supports saturation, shading and hue rotation natively.
+fn adjust_with_palette<C>(colour: C, adjustment: ColourAdjustment, amount: f32) -> C
+where
+    C: Saturate<Scalar = f32> + Shade<Scalar = f32> + Hue,
+    f32: Into<<C as GetHue>::Hue>,
+{
+    match adjustment {
+        ColourAdjustment::Saturate => colour.saturate(amount),
+        ColourAdjustment::Desaturate => colour.desaturate(amount),
+        ColourAdjustment::Lighten => colour.lighten(amount),
+        ColourAdjustment::Darken => colour.darken(amount),
+        ColourAdjustment::ShiftHue => colour.shift_hue(amount),
+    }
+}
+
+/// Apply an adjustment to a colour in the HWB colour space.
+/// HWB has no saturation axis, so saturating mixes in less white and black, and desaturating mixes in more.
+fn adjust_hwb(hwb: Hwb, adjustment: ColourAdjustment, amount: f32) -> Hwb {
+    let factor = match adjustment {
+        ColourAdjustment::Saturate => 1.0 - amount,
+        ColourAdjustment::Desaturate => 1.0 + amount,
+        ColourAdjustment::Lighten => return hwb.lighten(amount),
+        ColourAdjustment::Darken => return hwb.darken(amount),
+        ColourAdjustment::ShiftHue => return hwb.shift_hue(amount),
+    };
+
+    let mut hwb = hwb;
+    hwb.whiteness = (hwb.whiteness * factor).max(0.0);
+    hwb.blackness = (hwb.blackness * factor).max(0.0);
+
+    // Whiteness and blackness can't add up to more than 1
+    let total = hwb.whiteness + hwb.blackness;
+    if total > 1.0 {
+        hwb.whiteness /= total;
+        hwb.blackness /= total;
+    }
+    hwb
+}
+
+/// Apply an adjustment to the opponent colour axes (a, b) and lightness of a Lab-like colour.
+/// Saturation scales the distance from the neutral axis, and hue rotation turns the (a, b) vector.
+fn adjust_opponent(l: f32, a: f32, b: f32, l_range: f32, adjustment: ColourAdjustment, amount: f32) -> (f32, f32, f32) {
+    match adjustment {
+        ColourAdjustment::Saturate => (l, a * (1.0 + amount), b * (1.0 + amount)),
+        ColourAdjustment::Desaturate => (l, a * (1.0 - amount), b * (1.0 - amount)),
+        ColourAdjustment::Lighten => (l + amount * l_range, a, b),
+        ColourAdjustment::Darken => (l - amount * l_range, a, b),
+        ColourAdjustment::ShiftHue => {
+            let (sin, cos) = amount.to_radians().sin_cos();
+            (l, a * cos - b * sin, a * sin + b * cos)
+        }
+    }
+}
+
+/// Adjust a single RGBA pixel's colour in the given colour space. The pixel's alpha is preserved.
+///
+/// # Arguments
+/// * `px_data` - The RGBA values of the pixel.
+/// * `space` - The colour space in which to make the adjustment.
+/// * `adjustment` - The adjustment to make.
+/// * `amount` - The amount to adjust by. See `adjust` for the meaning of the amount for each adjustment.
+pub fn adjust_colour(px_data: [u8; 4], space: ColourSpace, adjustment: ColourAdjustment, amount: f32) -> [u8; 4] {
+    let linear = Srgb::from_raw(&px_data).into_format::<f32>().into_linear();
+
+    let new_linear: LinSrgb = match space {
+        ColourSpace::Lch => adjust_with_palette(Lch::from(linear), adjustment, amount).into(),
+        ColourSpace::Hsl => adjust_with_palette(Hsl::from(linear), adjustment, amount).into(),
+        ColourSpace::Hsv => adjust_with_palette(Hsv::from(linear), adjustment, amount).into(),
+        ColourSpace::Hwb => adjust_hwb(Hwb::from(linear), adjustment, amount).into(),
+        ColourSpace::Lab => {
+            let lab = Lab::from(linear);
+            let (l, a, b) = adjust_opponent(lab.l, lab.a, lab.b, 100.0, adjustment, amount);
+            Lab::new(l, a, b).into()
+        },
+        ColourSpace::Oklab => {
+            let oklab = Oklab::from(linear);
+            let (l, a, b) = adjust_opponent(oklab.l, oklab.a, oklab.b, 1.0, adjustment, amount);
+            Oklab::new(l, a, b).into()
+        },
+        ColourSpace::Oklch => adjust_with_palette(Oklch::from(linear), adjustment, amount).into(),
+    };
+
+    let new_data: [u8; 3] = Srgb::from_linear(new_linear).into_format().into_raw();
+    [new_data[0], new_data[1], new_data[2], px_data[3]]
+}
+
+/// Adjust the colours of an image in a chosen colour space.
+///
+/// This is the general form of the colour space functions in this module, such as `saturate_hsl`
+/// or `darken_lch`, which all call through to it.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `space` - The colour space in which to make the adjustment. See `ColourSpace`.
+/// * `adjustment` - The adjustment to make. See `ColourAdjustment`.
+/// * `amount` - For `ShiftHue`, the number of degrees to rotate the hue by. For all other adjustments,
+/// a float value from 0 to 1 which represents the amount the effect should be increased by.
+///
+/// # Example
+/// ```
+/// // For example to increase the saturation by 10% in the Oklab colour space:
+/// use photon::colour_spaces::{adjust, ColourSpace, ColourAdjustment};
+///
+/// adjust(&mut img, ColourSpace::Oklab, ColourAdjustment::Saturate, 0.1);
+/// ```
+#[wasm_bindgen]
+pub fn adjust(photon_image: &mut PhotonImage, space: ColourSpace, adjustment: ColourAdjustment, amount: f32) {
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        let new_data = adjust_colour([px[0], px[1], px[2], px[3]], space, adjustment, amount);
+        px.copy_from_slice(&new_data);
+    }
+}
+
+/// Apply one of the string-named modes accepted by `lch`, `hsl` and `hsv`.
+fn adjust_mode(photon_image: &mut PhotonImage, space: ColourSpace, mode: &str, amt: f32) -> Result<(), ColourSpaceError> {
+    let adjustment = ColourAdjustment::from_mode(mode).ok_or_else(|| ColourSpaceError::UnknownMode(mode.to_string()))?;
+    // The string-based API takes hue shifts as a fraction of a full turn
+    let amount = if adjustment == ColourAdjustment::ShiftHue { amt * 360.0 } else { amt };
+    adjust(photon_image, space, adjustment, amount);
+    Ok(())
+}
+
 /// Image manipulation effects in the LCh colour space
 /// 
 /// Effects include:
 /// * **saturate** - Saturation increase.
 /// * **desaturate** - Desaturate the image.
-/// * **shift_hue** - Hue rotation by a specified number of degrees.
+/// * **shift_hue** - Hue rotation by a specified fraction of a full turn.
 /// * **darken** - Decrease the brightness.
 /// * **lighten** - Increase the brightness.
 /// 
+/// Prefer `adjust`, which takes a typed `ColourAdjustment` instead of a mode string.
+/// 
 /// # Arguments
 /// * `photon_image` - A PhotonImage.
-/// * `mode` - The effect desired to be applied. Choose from: `saturate`, `desaturate`, `shift_hue`, `darken`, `lighten`
+/// * `mode` - The effect desired to be applied. Choose from: `saturate`, `desaturate`, `shift_hue`, `darken`, `lighten`.
+/// Any other value returns an error, leaving the image unchanged.
 /// * `amt` - A float value from 0 to 1 which represents the amount the effect should be increased by.
 /// # Example
 /// ```
@@ -71,37 +445,11 @@ use wasm_bindgen::prelude::*;
 /// // Open the image. A PhotonImage is returned.
 /// let img: PhotonImage = open_image("images/flowers.PNG");
 /// 
-/// lch(&mut img, "saturate", 0.1);
+/// lch(&mut img, "saturate", 0.1)?;
 /// ```
 #[wasm_bindgen]
-pub fn lch(mut photon_image: &mut PhotonImage, mode: &str, amt: f32) {
-    let img = helpers::dyn_image_from_raw(&photon_image);
-    let (width, height) = img.dimensions();
-    let mut img = img.to_rgba();
-    for x in 0..width {
-        for y in 0..height {
-            let px_data = img.get_pixel(x, y).data;
-            let lch_colour: Lch = Srgba::from_raw(&px_data)
-                .into_format()
-                .into_linear()
-                .into();
-
-            let new_color = match mode {
-                // Match a single value
-                "desaturate" => lch_colour.desaturate(amt),
-                "saturate" => lch_colour.saturate(amt),
-                "lighten" => lch_colour.lighten(amt), 
-                "darken" => lch_colour.darken(amt),
-                "shift_hue" => lch_colour.shift_hue(amt * 360.0),
-                _ => lch_colour.saturate(amt),
-            };
-            
-            img.put_pixel(x, y, image::Rgba {
-                data: Srgba::from_linear(new_color.into()).into_format().into_raw()
-            });
-            }
-        }
-    photon_image.raw_pixels = img.to_vec();
+pub fn lch(photon_image: &mut PhotonImage, mode: &str, amt: f32) -> Result<(), ColourSpaceError> {
+    adjust_mode(photon_image, ColourSpace::Lch, mode, amt)
 }
 
 /// Image manipulation effects in the HSL colour space.
@@ -109,13 +457,16 @@ pub fn lch(mut photon_image: &mut PhotonImage, mode: &str, amt: f32) {
 /// Effects include:
 /// * **saturate** - Saturation increase.
 /// * **desaturate** - Desaturate the image.
-/// * **shift_hue** - Hue rotation by a specified number of degrees.
+/// * **shift_hue** - Hue rotation by a specified fraction of a full turn.
 /// * **darken** - Decrease the brightness.
 /// * **lighten** - Increase the brightness.
 /// 
+/// Prefer `adjust`, which takes a typed `ColourAdjustment` instead of a mode string.
+/// 
 /// # Arguments
 /// * `photon_image` - A PhotonImage.
-/// * `mode` - The effect desired to be applied. Choose from: `saturate`, `desaturate`, `shift_hue`, `darken`, `lighten`
+/// * `mode` - The effect desired to be applied. Choose from: `saturate`, `desaturate`, `shift_hue`, `darken`, `lighten`.
+/// Any other value returns an error, leaving the image unchanged.
 /// * `amt` - A float value from 0 to 1 which represents the amount the effect should be increased by.
 /// # Example
 /// ```
@@ -125,39 +476,11 @@ pub fn lch(mut photon_image: &mut PhotonImage, mode: &str, amt: f32) {
 /// // Open the image. A PhotonImage is returned.
 /// let img: PhotonImage = open_image("images/flowers.PNG");
 /// 
-/// hsl(&mut img, "saturate", 0.1);
+/// hsl(&mut img, "saturate", 0.1)?;
 /// ``` 
 #[wasm_bindgen]
-pub fn hsl(mut photon_image: &mut PhotonImage, mode: &str, amt: f32) {
-    // The function logic is kept separate from other colour spaces for now, 
-    // since other HSL-specific logic may be implemented here, which isn't available in other colour spaces
-    let mut img = helpers::dyn_image_from_raw(&photon_image).to_rgba();
-    let (width, height) = img.dimensions();
-        for x in 0..width {
-            for y in 0..height {
-                let px_data = img.get_pixel(x, y).data;
-
-                let colour = Srgba::from_raw(&px_data).into_format();
-
-                let hsl_colour = Hsl::from(colour);
-                
-                let new_color = match mode {
-                    // Match a single value
-                    "desaturate" => hsl_colour.desaturate(amt),
-                    "saturate" => hsl_colour.saturate(amt),
-                    "lighten" => hsl_colour.lighten(amt), 
-                    "darken" => hsl_colour.darken(amt),
-                    "shift_hue" => hsl_colour.shift_hue(amt * 360.0),
-                    _ => hsl_colour.saturate(amt),
-                };
-
-                img.put_pixel(x, y, image::Rgba {
-                    data: Srgba::from_linear(new_color.into()).into_format().into_raw()
-                });
-            }
-        }
-
-    photon_image.raw_pixels = img.to_vec();
+pub fn hsl(photon_image: &mut PhotonImage, mode: &str, amt: f32) -> Result<(), ColourSpaceError> {
+    adjust_mode(photon_image, ColourSpace::Hsl, mode, amt)
 }
 
 /// Image manipulation in the HSV colour space. 
@@ -165,13 +488,16 @@ pub fn hsl(mut photon_image: &mut PhotonImage, mode: &str, amt: f32) {
 /// Effects include:
 /// * **saturate** - Saturation increase.
 /// * **desaturate** - Desaturate the image.
-/// * **shift_hue** - Hue rotation by a specified number of degrees.
+/// * **shift_hue** - Hue rotation by a specified fraction of a full turn.
 /// * **darken** - Decrease the brightness.
 /// * **lighten** - Increase the brightness.
 /// 
+/// Prefer `adjust`, which takes a typed `ColourAdjustment` instead of a mode string.
+/// 
 /// # Arguments
 /// * `photon_image` - A PhotonImage.
-/// * `mode` - The effect desired to be applied. Choose from: `saturate`, `desaturate`, `shift_hue`, `darken`, `lighten`
+/// * `mode` - The effect desired to be applied. Choose from: `saturate`, `desaturate`, `shift_hue`, `darken`, `lighten`.
+/// Any other value returns an error, leaving the image unchanged.
 /// * `amt` - A float value from 0 to 1 which represents the amount the effect should be increased by.
 /// 
 /// # Example
@@ -182,45 +508,17 @@ pub fn hsl(mut photon_image: &mut PhotonImage, mode: &str, amt: f32) {
 /// // Open the image. A PhotonImage is returned.
 /// let img: PhotonImage = open_image("images/flowers.PNG");
 /// 
-/// hsv(&mut img, "saturate", 0.1);
+/// hsv(&mut img, "saturate", 0.1)?;
 /// ```
 #[wasm_bindgen]
-pub fn hsv(photon_image: &mut PhotonImage, mode: &str, amt: f32) {
-    let img = helpers::dyn_image_from_raw(&photon_image);
-    let mut img  = img.to_rgba();
-
-    let (width, height) = img.dimensions();
-
-        for x in 0..width {
-            for y in 0..height {
-                let px_data = img.get_pixel(x, y).data;
-
-                let color = Srgba::from_raw(&px_data).into_format();
-
-                let hsv_colour = Hsv::from(color);
-
-                let new_color = match mode {
-                    // Match a single value
-                    "desaturate" => hsv_colour.desaturate(amt),
-                    "saturate" => hsv_colour.saturate(amt),
-                    "lighten" => hsv_colour.lighten(amt), 
-                    "darken" => hsv_colour.darken(amt),
-                    "shift_hue" => hsv_colour.shift_hue(amt * 360.0),
-                    _ => hsv_colour.saturate(amt),
-                };
-
-                img.put_pixel(x, y, image::Rgba {
-                    data: Srgba::from_linear(new_color.into()).into_format().into_raw()
-                });
-            }
-        }
-    photon_image.raw_pixels = img.to_vec();
+pub fn hsv(photon_image: &mut PhotonImage, mode: &str, amt: f32) -> Result<(), ColourSpaceError> {
+    adjust_mode(photon_image, ColourSpace::Hsv, mode, amt)
 }
 
-/// Shift hue by a specified number of degrees in the HSL colour space.
+/// Shift hue by a fraction of a full turn in the HSL colour space.
 /// # Arguments
 /// * `img` - A PhotonImage.
-/// * `mode` - The number of degrees to shift the hue by, or hue rotate by.
+/// * `turns` - The fraction of a full turn to rotate the hue by, so 0.5 rotates it by 180 degrees.
 /// 
 /// # Example
 /// ```
@@ -230,17 +528,17 @@ pub fn hsv(photon_image: &mut PhotonImage, mode: &str, amt: f32) {
 /// // Open the image. A PhotonImage is returned.
 /// let img: PhotonImage = open_image("images/flowers.PNG");
 /// 
-/// hue_rotate_hsl(&mut img, 120);
+/// hue_rotate_hsl(&mut img, 1.0 / 3.0);
 /// ``` 
 #[wasm_bindgen]
-pub fn hue_rotate_hsl(img: &mut PhotonImage, degrees: f32) {
-    hsl(img, "shift_hue", degrees);
+pub fn hue_rotate_hsl(img: &mut PhotonImage, turns: f32) {
+    adjust(img, ColourSpace::Hsl, ColourAdjustment::ShiftHue, turns * 360.0);
 }
 
-/// Shift hue by a specified number of degrees in the HSV colour space.
+/// Shift hue by a fraction of a full turn in the HSV colour space.
 /// # Arguments
 /// * `img` - A PhotonImage.
-/// * `mode` - The number of degrees to shift the hue by, or hue rotate by.
+/// * `turns` - The fraction of a full turn to rotate the hue by, so 0.5 rotates it by 180 degrees.
 /// 
 /// # Example
 /// ```
@@ -250,17 +548,17 @@ pub fn hue_rotate_hsl(img: &mut PhotonImage, degrees: f32) {
 /// // Open the image. A PhotonImage is returned.
 /// let img: PhotonImage = open_image("images/flowers.PNG");
 /// 
-/// hue_rotate_hsv(&mut img, 120);
+/// hue_rotate_hsv(&mut img, 1.0 / 3.0);
 /// ``` 
 #[wasm_bindgen]
-pub fn hue_rotate_hsv(img: &mut PhotonImage, degrees: f32) {
-    hsv(img, "shift_hue", degrees);
+pub fn hue_rotate_hsv(img: &mut PhotonImage, turns: f32) {
+    adjust(img, ColourSpace::Hsv, ColourAdjustment::ShiftHue, turns * 360.0);
 }
 
-/// Shift hue by a specified number of degrees in the LCh colour space.
+/// Shift hue by a fraction of a full turn in the LCh colour space.
 /// # Arguments
 /// * `img` - A PhotonImage.
-/// * `mode` - The number of degrees to shift the hue by, or hue rotate by.
+/// * `turns` - The fraction of a full turn to rotate the hue by, so 0.5 rotates it by 180 degrees.
 /// 
 /// # Example
 /// ```
@@ -270,11 +568,11 @@ pub fn hue_rotate_hsv(img: &mut PhotonImage, degrees: f32) {
 /// // Open the image. A PhotonImage is returned.
 /// let img: PhotonImage = open_image("images/flowers.PNG");
 /// 
-/// hue_rotate_lch(&mut img, 120);
+/// hue_rotate_lch(&mut img, 1.0 / 3.0);
 /// ``` 
 #[wasm_bindgen]
-pub fn hue_rotate_lch(img: &mut PhotonImage, degrees: f32) {
-    lch(img, "shift_hue", degrees)
+pub fn hue_rotate_lch(img: &mut PhotonImage, turns: f32) {
+    adjust(img, ColourSpace::Lch, ColourAdjustment::ShiftHue, turns * 360.0);
 }
 
 /// Increase the image's saturation by converting each pixel's colour to the HSL colour space
@@ -298,7 +596,7 @@ pub fn hue_rotate_lch(img: &mut PhotonImage, degrees: f32) {
 #[wasm_bindgen]
 pub fn saturate_hsl(img: &mut PhotonImage, level: f32) {
 
-    return hsl(img, "saturate", level);
+    adjust(img, ColourSpace::Hsl, ColourAdjustment::Saturate, level);
 }
 
 /// Increase the image's saturation in the LCh colour space.
@@ -320,7 +618,7 @@ pub fn saturate_hsl(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn saturate_lch(img: &mut PhotonImage, level: f32) {
-    return lch(img, "saturate", level);
+    adjust(img, ColourSpace::Lch, ColourAdjustment::Saturate, level);
 }
 
 /// Increase the image's saturation in the HSV colour space.
@@ -342,7 +640,7 @@ pub fn saturate_lch(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn saturate_hsv(img: &mut PhotonImage, level: f32) {
-    return hsv(img, "saturate", level);
+    adjust(img, ColourSpace::Hsv, ColourAdjustment::Saturate, level);
 }
 
 /// Lighten an image by a specified amount in the LCh colour space.
@@ -365,7 +663,7 @@ pub fn saturate_hsv(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn lighten_lch(img: &mut PhotonImage, level: f32) {
-    return lch(img, "lighten", level);
+    adjust(img, ColourSpace::Lch, ColourAdjustment::Lighten, level);
 }
 
 /// Lighten an image by a specified amount in the HSL colour space.
@@ -387,7 +685,7 @@ pub fn lighten_lch(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn lighten_hsl(img: &mut PhotonImage, level: f32) {
-    return hsl(img, "lighten", level);
+    adjust(img, ColourSpace::Hsl, ColourAdjustment::Lighten, level);
 }
 
 /// Lighten an image by a specified amount in the HSV colour space.
@@ -410,7 +708,7 @@ pub fn lighten_hsl(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn lighten_hsv(img: &mut PhotonImage, level: f32) {
-    return hsv(img, "lighten", level);
+    adjust(img, ColourSpace::Hsv, ColourAdjustment::Lighten, level);
 }
 
 /// Darken the image by a specified amount in the LCh colour space.
@@ -433,7 +731,7 @@ pub fn lighten_hsv(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn darken_lch(img: &mut PhotonImage, level: f32) {
-    return lch(img, "darken", level);
+    adjust(img, ColourSpace::Lch, ColourAdjustment::Darken, level);
 }
 
 /// Darken the image by a specified amount in the HSL colour space.
@@ -456,7 +754,7 @@ pub fn darken_lch(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn darken_hsl(img: &mut PhotonImage, level: f32) {
-    return hsl(img, "darken", level);
+    adjust(img, ColourSpace::Hsl, ColourAdjustment::Darken, level);
 }
 
 /// Darken the image's colours by a specified amount in the HSV colour space.
@@ -479,7 +777,7 @@ pub fn darken_hsl(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn darken_hsv(img: &mut PhotonImage, level: f32) {
-    return hsv(img, "darken", level);
+    adjust(img, ColourSpace::Hsv, ColourAdjustment::Darken, level);
 }
 
 /// Desaturate the image by a specified amount in the HSV colour space.
@@ -502,7 +800,7 @@ pub fn darken_hsv(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn desaturate_hsv(img: &mut PhotonImage, level: f32) {
-    return hsv(img, "desaturate", level);
+    adjust(img, ColourSpace::Hsv, ColourAdjustment::Desaturate, level);
 }
 
 /// Desaturate the image by a specified amount in the HSL colour space.
@@ -525,7 +823,7 @@ pub fn desaturate_hsv(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn desaturate_hsl(img: &mut PhotonImage, level: f32) {
-    return hsl(img, "desaturate", level);
+    adjust(img, ColourSpace::Hsl, ColourAdjustment::Desaturate, level);
 }
 
 /// Desaturate the image by a specified amount in the LCh colour space.
@@ -548,7 +846,278 @@ pub fn desaturate_hsl(img: &mut PhotonImage, level: f32) {
 /// ``` 
 #[wasm_bindgen]
 pub fn desaturate_lch(img: &mut PhotonImage, level: f32) {
-    return lch(img, "desaturate", level);
+    adjust(img, ColourSpace::Lch, ColourAdjustment::Desaturate, level);
+}
+
+/// Shift hue by a specified number of degrees in the Oklch colour space.
+/// 
+/// Unlike LCh, hue rotations in Oklch keep blues from drifting towards purple.
+/// # Arguments
+/// * `img` - A PhotonImage.
+/// * `degrees` - The number of degrees to shift the hue by, or hue rotate by.
+/// 
+/// # Example
+/// ```
+/// // For example to hue rotate/shift the hue by 120 degrees in the Oklch colour space:
+/// use photon::colour_spaces::hue_rotate_oklch;
+/// 
+/// // Open the image. A PhotonImage is returned.
+/// let img: PhotonImage = open_image("images/flowers.PNG");
+/// 
+/// hue_rotate_oklch(&mut img, 120.0);
+/// ``` 
+#[wasm_bindgen]
+pub fn hue_rotate_oklch(img: &mut PhotonImage, degrees: f32) {
+    adjust(img, ColourSpace::Oklch, ColourAdjustment::ShiftHue, degrees);
+}
+
+/// Increase the image's saturation in the Oklch colour space.
+/// # Arguments
+/// * `img` - A PhotonImage.
+/// * `level` - Float value from 0 to 1 representing the level to which to increase the saturation by.
+/// The `level` must be from 0 to 1 in floating-point, `f32` format. 
+/// Increasing saturation by 80% would be represented by a `level` of 0.8
+/// 
+/// # Example
+/// ```
+/// // For example to increase saturation by 40% in the Oklch colour space:
+/// use photon::colour_spaces::saturate_oklch;
+/// 
+/// // Open the image. A PhotonImage is returned.
+/// let img: PhotonImage = open_image("images/flowers.PNG");
+/// 
+/// saturate_oklch(&mut img, 0.4);
+/// ``` 
+#[wasm_bindgen]
+pub fn saturate_oklch(img: &mut PhotonImage, level: f32) {
+    adjust(img, ColourSpace::Oklch, ColourAdjustment::Saturate, level);
+}
+
+/// Desaturate the image by a specified amount in the Oklch colour space.
+/// 
+/// # Arguments
+/// * `img` - A PhotonImage.
+/// * `level` - Float value from 0 to 1 representing the level to which to desaturate the image by.
+/// The `level` must be from 0 to 1 in floating-point, `f32` format. 
+/// Desaturating by 80% would be represented by a `level` of 0.8
+/// 
+/// # Example
+/// ```
+/// // For example to desaturate an image by 10% in the Oklch colour space:
+/// use photon::colour_spaces::desaturate_oklch;
+/// 
+/// // Open the image. A PhotonImage is returned.
+/// let img: PhotonImage = open_image("images/flowers.PNG");
+/// 
+/// desaturate_oklch(&mut img, 0.1);
+/// ``` 
+#[wasm_bindgen]
+pub fn desaturate_oklch(img: &mut PhotonImage, level: f32) {
+    adjust(img, ColourSpace::Oklch, ColourAdjustment::Desaturate, level);
+}
+
+/// Lighten an image by a specified amount in the Oklch colour space.
+/// 
+/// # Arguments
+/// * `img` - A PhotonImage.
+/// * `level` - Float value from 0 to 1 representing the level to which to lighten the image by.
+/// The `level` must be from 0 to 1 in floating-point, `f32` format. 
+/// Lightening by 80% would be represented by a `level` of 0.8
+/// 
+/// # Example
+/// ```
+/// // For example to lighten an image by 10% in the Oklch colour space:
+/// use photon::colour_spaces::lighten_oklch;
+/// 
+/// // Open the image. A PhotonImage is returned.
+/// let img: PhotonImage = open_image("images/flowers.PNG");
+/// 
+/// lighten_oklch(&mut img, 0.1);
+/// ``` 
+#[wasm_bindgen]
+pub fn lighten_oklch(img: &mut PhotonImage, level: f32) {
+    adjust(img, ColourSpace::Oklch, ColourAdjustment::Lighten, level);
+}
+
+/// Darken the image by a specified amount in the Oklch colour space.
+/// 
+/// # Arguments
+/// * `img` - A PhotonImage.
+/// * `level` - Float value from 0 to 1 representing the level to which to darken the image by.
+/// The `level` must be from 0 to 1 in floating-point, `f32` format. 
+/// Darkening by 80% would be represented by a `level` of 0.8
+/// 
+/// # Example
+/// ```
+/// // For example to darken an image by 10% in the Oklch colour space:
+/// use photon::colour_spaces::darken_oklch;
+/// 
+/// // Open the image. A PhotonImage is returned.
+/// let img: PhotonImage = open_image("images/flowers.PNG");
+/// 
+/// darken_oklch(&mut img, 0.1);
+/// ``` 
+#[wasm_bindgen]
+pub fn darken_oklch(img: &mut PhotonImage, level: f32) {
+    adjust(img, ColourSpace::Oklch, ColourAdjustment::Darken, level);
+}
+
+/// Colour bands targeted by the HSL panel, named after the colours they cover.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColourBand {
+    Reds = 0,
+    Oranges = 1,
+    Yellows = 2,
+    Greens = 3,
+    Aquas = 4,
+    Blues = 5,
+    Purples = 6,
+    Magentas = 7,
+}
+
+/// The hue (in degrees on the HSL colour wheel) at the centre of each colour band, in `ColourBand` order.
+const BAND_HUES: [f32; 8] = [0.0, 30.0, 60.0, 120.0, 180.0, 240.0, 270.0, 300.0];
+
+/// Per-band hue, saturation and luminance adjustments, as found in the HSL panel of photo editors.
+///
+/// Each colour band can be adjusted independently. Pixels whose hue falls between two band centres
+/// receive a feathered mix of both bands' adjustments, so there are no hard transitions between bands.
+/// Pass a panel to `hsl_panel` to apply it to an image.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HslPanel {
+    hue: [f32; 8],
+    saturation: [f32; 8],
+    luminance: [f32; 8],
+}
+
+#[wasm_bindgen]
+impl HslPanel {
+    #[wasm_bindgen(constructor)]
+    /// Create a new HSL panel with no adjustments.
+    pub fn new() -> HslPanel {
+        HslPanel::default()
+    }
+
+    /// Set the number of degrees, from -180 to 180, by which to shift the hue of a colour band.
+    pub fn set_hue(&mut self, band: ColourBand, degrees: f32) {
+        self.hue[band as usize] = num::clamp(degrees, -180.0, 180.0);
+    }
+
+    /// Set the saturation adjustment of a colour band, from -1 (fully desaturated) to 1 (doubled saturation).
+    pub fn set_saturation(&mut self, band: ColourBand, amount: f32) {
+        self.saturation[band as usize] = num::clamp(amount, -1.0, 1.0);
+    }
+
+    /// Set the luminance adjustment of a colour band, from -1 (black) to 1 (doubled lightness).
+    pub fn set_luminance(&mut self, band: ColourBand, amount: f32) {
+        self.luminance[band as usize] = num::clamp(amount, -1.0, 1.0);
+    }
+
+    /// Get the hue shift of a colour band, in degrees.
+    pub fn get_hue(&self, band: ColourBand) -> f32 {
+        self.hue[band as usize]
+    }
+
+    /// Get the saturation adjustment of a colour band.
+    pub fn get_saturation(&self, band: ColourBand) -> f32 {
+        self.saturation[band as usize]
+    }
+
+    /// Get the luminance adjustment of a colour band.
+    pub fn get_luminance(&self, band: ColourBand) -> f32 {
+        self.luminance[band as usize]
+    }
+}
+
+/// Get how much each colour band contributes at the given hue.
+/// Hues between two band centres are shared between those two bands with a smoothstep feather,
+/// and the returned weights always sum to 1.
+fn band_weights(hue: f32) -> [f32; 8] {
+    let hue = hue.rem_euclid(360.0);
+    let mut weights = [0.0; 8];
+
+    for i in 0..BAND_HUES.len() {
+        let next = (i + 1) % BAND_HUES.len();
+        let start = BAND_HUES[i];
+        // The last band (magentas) wraps around to the reds at 360 degrees
+        let end = if next == 0 { 360.0 } else { BAND_HUES[next] };
+
+        if hue >= start && hue < end {
+            let t = (hue - start) / (end - start);
+            let t = t * t * (3.0 - 2.0 * t);
+            weights[i] = 1.0 - t;
+            weights[next] = t;
+            break;
+        }
+    }
+    weights
+}
+
+/// Adjust the hue, saturation and luminance of individual colour bands (reds, oranges, yellows, greens,
+/// aquas, blues, purples and magentas), like the HSL panel of a photo editor.
+///
+/// Bands are selected by HSL hue, and hue and saturation changes are made in the HSL colour space.
+/// Luminance changes are made to the lightness of the LCh colour space, so that brightening or darkening
+/// a band keeps its hue intact. Near-grey pixels, which have no meaningful hue, are left mostly untouched.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `panel` - The per-band adjustments to apply. See `HslPanel`.
+///
+/// # Example
+/// ```
+/// // For example, to make the sky a deeper blue and the foliage more muted:
+/// use photon::colour_spaces::{hsl_panel, HslPanel, ColourBand};
+///
+/// let mut panel = HslPanel::new();
+/// panel.set_saturation(ColourBand::Blues, 0.3);
+/// panel.set_luminance(ColourBand::Blues, -0.2);
+/// panel.set_saturation(ColourBand::Greens, -0.4);
+///
+/// hsl_panel(&mut img, &panel);
+/// ```
+#[wasm_bindgen]
+pub fn hsl_panel(photon_image: &mut PhotonImage, panel: &HslPanel) {
+    let mut img = helpers::dyn_image_from_raw(photon_image).to_rgba();
+    let (width, height) = img.dimensions();
+
+    for x in 0..width {
+        for y in 0..height {
+            let px_data = img.get_pixel(x, y).data;
+
+            // The gamma-encoded values are treated as linear here, so that the hues match the familiar
+            // (CSS-style) HSL colour wheel which the band centres are defined on.
+            let rgb: Srgb = Srgb::from_raw(&px_data).into_format();
+            let hsl_colour = Hsl::from(LinSrgb::new(rgb.red, rgb.green, rgb.blue));
+
+            // Hue is meaningless for greys, so fade the adjustments out as saturation approaches zero
+            let strength = num::clamp(hsl_colour.saturation / 0.2, 0.0, 1.0);
+            if strength == 0.0 {
+                continue;
+            }
+
+            let weights = band_weights(hsl_colour.hue.to_positive_degrees());
+            let (mut hue_shift, mut sat, mut lum) = (0.0, 0.0, 0.0);
+            for (band, weight) in weights.iter().enumerate() {
+                hue_shift += weight * panel.hue[band];
+                sat += weight * panel.saturation[band];
+                lum += weight * panel.luminance[band];
+            }
+
+            let mut hsl_colour = hsl_colour.shift_hue(hue_shift * strength);
+            hsl_colour.saturation = num::clamp(hsl_colour.saturation * (1.0 + sat * strength), 0.0, 1.0);
+
+            let shifted = LinSrgb::from(hsl_colour);
+            let mut lch_colour: Lch = Srgb::new(shifted.red, shifted.green, shifted.blue).into_linear().into();
+            lch_colour.l = num::clamp(lch_colour.l * (1.0 + lum * strength), 0.0, 100.0);
+
+            let new_data: [u8; 3] = Srgb::from_linear(lch_colour.into()).into_format().into_raw();
+            img.put_pixel(x, y, image::Rgba([new_data[0], new_data[1], new_data[2], px_data[3]]));
+        }
+    }
+    photon_image.raw_pixels = img.to_vec();
 }
 
 /// Mix image with a single color, supporting passing `opacity`.
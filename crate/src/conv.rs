@@ -4,6 +4,7 @@ extern crate image;
 use wasm_bindgen::prelude::*;
 use crate::{PhotonImage};
 use crate::helpers;
+use crate::denoise;
 
 fn conv(mut photon_image: &mut PhotonImage, kernel: Vec<f32>) {
     let img = helpers::dyn_image_from_raw(&photon_image);
@@ -12,38 +13,38 @@ fn conv(mut photon_image: &mut PhotonImage, kernel: Vec<f32>) {
     photon_image.raw_pixels = filtered_img.raw_pixels();
 }
 
-/// Noise reduction. 
-/// 
+/// Noise reduction.
+///
+/// Applies a 3x3 median filter, which removes speckle noise while keeping edges sharp.
+/// For finer control, see the filters in the `denoise` module.
+///
 /// # Arguments
-/// * `img` - A DynamicImage that contains a view into the image.
-/// 
+/// * `img` - A PhotonImage that contains a view into the image.
+///
 /// # Example
 ///
 /// ```
-/// // For example, to sharpen an image:
-/// use photon::conv::sharpen;
-/// photon::channels::sharpen(img);
+/// // For example, to reduce the noise in an image:
+/// use photon::conv::noise_reduction;
+/// noise_reduction(&mut img);
 /// ```
-/// Adds a constant to a select R, G, or B channel's value.
 #[wasm_bindgen]
 pub fn noise_reduction(photon_image: &mut PhotonImage) {
-    let kernel = vec![0.0f32, -1.0, 7.0, -1.0, 5.0, 9.0, 0.0, 7.0, 9.0];
-    return conv(photon_image, kernel);
+    denoise::median_filter(photon_image, 1);
 }
 
-/// Sharpen an image. 
+/// Sharpen an image with a fixed 3x3 kernel. For control over the strength, see `unsharp_mask`.
 /// 
 /// # Arguments
-/// * `img` - A DynamicImage that contains a view into the image.
+/// * `img` - A PhotonImage that contains a view into the image.
 /// 
 /// # Example
 ///
 /// ```
 /// // For example, to sharpen an image:
 /// use photon::conv::sharpen;
-/// photon::channels::sharpen(img);
+/// sharpen(&mut img);
 /// ```
-/// Adds a constant to a select R, G, or B channel's value.
 #[wasm_bindgen]
 pub fn sharpen(photon_image: &mut PhotonImage) {
     let kernel = vec![0.0f32, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0];
@@ -302,6 +303,160 @@ pub fn sobel_vertical(photon_image: &mut PhotonImage) {
     return conv(photon_image, kernel);
 }
 
+/// Get the luma of an RGB colour, using the Rec. 709 weights.
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// Gaussian blur the R, G and B channels of an image with a separable kernel, keeping the result as
+/// floats so that flat areas stay exactly flat. Pixels beyond the edges repeat the edge pixels.
+/// Returns three values per pixel.
+pub(crate) fn gaussian_blur_rgb(photon_image: &PhotonImage, sigma: f32) -> Vec<f32> {
+    let width = photon_image.width as usize;
+    let height = photon_image.height as usize;
+    let radius = (sigma * 3.0).ceil() as isize;
+    let mut kernel: Vec<f32> = (-radius..=radius).map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp()).collect();
+    let total: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|weight| *weight /= total);
+
+    let input: Vec<f32> = photon_image.raw_pixels.chunks(4)
+        .flat_map(|px| vec![px[0] as f32, px[1] as f32, px[2] as f32])
+        .collect();
+
+    let pass = |input: &[f32], horizontal: bool| -> Vec<f32> {
+        let mut output = vec![0.0; input.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sums = [0.0f32; 3];
+                for (k, weight) in kernel.iter().enumerate() {
+                    let offset = k as isize - radius;
+                    let (sx, sy) = if horizontal {
+                        (num::clamp(x as isize + offset, 0, width as isize - 1) as usize, y)
+                    } else {
+                        (x, num::clamp(y as isize + offset, 0, height as isize - 1) as usize)
+                    };
+                    let j = (sy * width + sx) * 3;
+                    for (channel, sum) in sums.iter_mut().enumerate() {
+                        *sum += input[j + channel] * weight;
+                    }
+                }
+                output[(y * width + x) * 3..(y * width + x) * 3 + 3].copy_from_slice(&sums);
+            }
+        }
+        output
+    };
+    pass(&pass(&input, true), false)
+}
+
+/// Sharpen an image with an unsharp mask, which adds back the difference between the image and a
+/// Gaussian-blurred copy of it.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// * `radius` - The standard deviation of the Gaussian blur, in pixels. Small values sharpen fine detail, larger values add punch.
+/// * `amount` - How much of the difference to add back. 1.0 doubles the contrast of edges.
+/// * `threshold` - The minimum difference, from 0 to 255, that is sharpened. Raising this avoids sharpening noise and skin texture.
+/// * `luminance_only` - Whether to sharpen only the brightness, rather than each channel separately.
+/// This avoids colour fringes along edges between saturated colours.
+///
+/// # Example
+///
+/// ```
+/// // For example, to sharpen a photo's luminance with a 1.5 pixel radius:
+/// use photon::conv::unsharp_mask;
+/// unsharp_mask(&mut img, 1.5, 0.8, 3, true);
+/// ```
+#[wasm_bindgen]
+pub fn unsharp_mask(photon_image: &mut PhotonImage, radius: f32, amount: f32, threshold: u8, luminance_only: bool) {
+    if radius <= 0.0 {
+        panic!("The unsharp mask radius must be greater than 0");
+    }
+    let blurred = gaussian_blur_rgb(photon_image, radius);
+
+    for (px, blurred_px) in photon_image.raw_pixels.chunks_mut(4).zip(blurred.chunks(3)) {
+        if luminance_only {
+            let difference = luma(px[0] as f32, px[1] as f32, px[2] as f32) - luma(blurred_px[0], blurred_px[1], blurred_px[2]);
+            if difference.abs() < threshold as f32 {
+                continue;
+            }
+            // Shifting every channel by the same amount changes the brightness but not the hue
+            for channel in px.iter_mut().take(3) {
+                *channel = num::clamp((*channel as f32 + amount * difference).round(), 0.0, 255.0) as u8;
+            }
+        }
+        else {
+            for (channel, blurred_channel) in px.iter_mut().zip(blurred_px.iter()).take(3) {
+                let difference = *channel as f32 - blurred_channel;
+                if difference.abs() >= threshold as f32 {
+                    *channel = num::clamp((*channel as f32 + amount * difference).round(), 0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Apply a high-pass filter, which keeps only the detail smaller than `radius` on a mid-grey background.
+///
+/// The result is useful as a layer for `multiple::blend` with the overlay or soft light modes, for sharpening or local contrast.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// * `radius` - The standard deviation of the Gaussian blur that is subtracted, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to extract the fine detail of an image:
+/// use photon::conv::high_pass;
+/// high_pass(&mut img, 3.0);
+/// ```
+#[wasm_bindgen]
+pub fn high_pass(photon_image: &mut PhotonImage, radius: f32) {
+    if radius <= 0.0 {
+        panic!("The high pass radius must be greater than 0");
+    }
+    let blurred = gaussian_blur_rgb(photon_image, radius);
+
+    for (px, blurred_px) in photon_image.raw_pixels.chunks_mut(4).zip(blurred.chunks(3)) {
+        for (channel, blurred_channel) in px.iter_mut().zip(blurred_px.iter()) {
+            *channel = num::clamp((128.0 + *channel as f32 - blurred_channel).round(), 0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Adjust clarity, the local contrast of midtones.
+///
+/// This is a large-radius unsharp mask on luminance, weighted towards the midtones so that highlights
+/// and shadows don't clip. It adds depth and texture without the halos of ordinary sharpening.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// * `amount` - The adjustment, from -1 to 1. Positive values add local contrast, negative values soften the image.
+///
+/// # Example
+///
+/// ```
+/// // For example, to add some clarity to a landscape:
+/// use photon::conv::clarity;
+/// clarity(&mut img, 0.4);
+/// ```
+#[wasm_bindgen]
+pub fn clarity(photon_image: &mut PhotonImage, amount: f32) {
+    let amount = num::clamp(amount, -1.0, 1.0);
+    // The radius scales with the image, so that the effect looks the same at any resolution
+    let radius = (photon_image.width.max(photon_image.height) as f32 / 100.0).max(2.0);
+    let blurred = gaussian_blur_rgb(photon_image, radius);
+
+    for (px, blurred_px) in photon_image.raw_pixels.chunks_mut(4).zip(blurred.chunks(3)) {
+        let y = luma(px[0] as f32, px[1] as f32, px[2] as f32);
+        let midtones = 1.0 - ((y / 255.0 - 0.5) * 2.0).powi(2);
+        let shift = amount * midtones * (y - luma(blurred_px[0], blurred_px[1], blurred_px[2]));
+        for channel in px.iter_mut().take(3) {
+            *channel = num::clamp((*channel as f32 + shift).round(), 0.0, 255.0) as u8;
+        }
+    }
+}
+
 
 // // pub fn sobel_color(img: DynamicImage) -> DynamicImage {
 
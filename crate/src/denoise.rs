@@ -0,0 +1,301 @@
+//! Noise reduction and edge-preserving smoothing, including median, bilateral, guided and non-local means filters.
+//!
+//! All filters leave the alpha channel untouched.
+
+use wasm_bindgen::prelude::*;
+use crate::PhotonImage;
+
+/// Compute the sum of a plane of values over the `(2 * radius + 1)` square around every pixel, using a summed-area table.
+/// Squares are clipped at the image's edges; the number of pixels each square covers is returned alongside the sums.
+fn box_sums(plane: &[f32], width: usize, height: usize, radius: usize) -> (Vec<f32>, Vec<f32>) {
+    let stride = width + 1;
+    // Accumulate in f64, as f32 sums lose precision over large images
+    let mut table = vec![0.0f64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row = 0.0;
+        for x in 0..width {
+            row += plane[y * width + x] as f64;
+            table[(y + 1) * stride + x + 1] = table[y * stride + x + 1] + row;
+        }
+    }
+
+    let mut sums = Vec::with_capacity(width * height);
+    let mut areas = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let (y0, y1) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (x0, x1) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = table[y1 * stride + x1] - table[y0 * stride + x1] - table[y1 * stride + x0] + table[y0 * stride + x0];
+            sums.push(sum as f32);
+            areas.push(((x1 - x0) * (y1 - y0)) as f32);
+        }
+    }
+    (sums, areas)
+}
+
+/// The mean of a plane of values over the `(2 * radius + 1)` square around every pixel.
+fn box_means(plane: &[f32], width: usize, height: usize, radius: usize) -> Vec<f32> {
+    let (sums, areas) = box_sums(plane, width, height, radius);
+    sums.iter().zip(areas.iter()).map(|(sum, area)| sum / area).collect()
+}
+
+/// Median filter. Replaces each channel value with the median of its neighbourhood, which removes
+/// speckle and salt-and-pepper noise while keeping edges sharp.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `radius` - The radius of the square neighbourhood, in pixels. A radius of 1 gives a 3x3 median.
+///
+/// # Example
+///
+/// ```
+/// // For example, to apply a 5x5 median filter:
+/// use photon::denoise::median_filter;
+/// median_filter(&mut img, 2);
+/// ```
+#[wasm_bindgen]
+pub fn median_filter(photon_image: &mut PhotonImage, radius: u32) {
+    let width = photon_image.width as usize;
+    let height = photon_image.height as usize;
+    let r = radius as usize;
+    let input = photon_image.raw_pixels.clone();
+
+    // Huang's algorithm: a histogram of the window is updated column by column as it slides along each row
+    for y in 0..height {
+        let (y0, y1) = (y.saturating_sub(r), (y + r + 1).min(height));
+        let mut histograms = [[0u32; 256]; 3];
+        let mut count = 0;
+
+        let add_column = |histograms: &mut [[u32; 256]; 3], x: usize, delta: i32| {
+            for row in y0..y1 {
+                let i = (row * width + x) * 4;
+                for (channel, histogram) in histograms.iter_mut().enumerate() {
+                    let bin = &mut histogram[input[i + channel] as usize];
+                    *bin = (*bin as i32 + delta) as u32;
+                }
+            }
+        };
+
+        for x in 0..r.min(width) {
+            add_column(&mut histograms, x, 1);
+            count += y1 - y0;
+        }
+
+        for x in 0..width {
+            if x + r < width {
+                add_column(&mut histograms, x + r, 1);
+                count += y1 - y0;
+            }
+            if x > r {
+                add_column(&mut histograms, x - r - 1, -1);
+                count -= y1 - y0;
+            }
+
+            let i = (y * width + x) * 4;
+            for (channel, histogram) in histograms.iter().enumerate() {
+                let mut seen = 0;
+                for (value, bin) in histogram.iter().enumerate() {
+                    seen += *bin as usize;
+                    if seen * 2 > count {
+                        photon_image.raw_pixels[i + channel] = value as u8;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bilateral filter. Averages each pixel with its neighbours, weighted both by distance and by
+/// colour similarity, so that flat areas are smoothed while edges are preserved.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `spatial_sigma` - The standard deviation of the spatial weights, in pixels. The neighbourhood radius is twice this.
+/// * `range_sigma` - The standard deviation of the colour weights, on the 0 to 255 channel scale.
+/// Colour differences much larger than this are treated as edges and aren't smoothed across.
+///
+/// # Example
+///
+/// ```
+/// // For example, to smooth skin in a portrait while keeping facial features:
+/// use photon::denoise::bilateral_filter;
+/// bilateral_filter(&mut img, 3.0, 25.0);
+/// ```
+#[wasm_bindgen]
+pub fn bilateral_filter(photon_image: &mut PhotonImage, spatial_sigma: f32, range_sigma: f32) {
+    if spatial_sigma <= 0.0 || range_sigma <= 0.0 {
+        panic!("The spatial and range sigmas must be greater than 0");
+    }
+    let width = photon_image.width as i32;
+    let height = photon_image.height as i32;
+    let radius = (spatial_sigma * 2.0).ceil() as i32;
+    let input = photon_image.raw_pixels.clone();
+
+    let mut spatial = vec![];
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let weight = (-((dx * dx + dy * dy) as f32) / (2.0 * spatial_sigma * spatial_sigma)).exp();
+            spatial.push((dx, dy, weight));
+        }
+    }
+    // Range weights for every possible squared colour distance
+    let range: Vec<f32> = (0..=3 * 255 * 255)
+        .map(|d| (-(d as f32) / (2.0 * range_sigma * range_sigma)).exp())
+        .collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = ((y * width + x) * 4) as usize;
+            let mut totals = [0.0f32; 3];
+            let mut weights = 0.0;
+
+            for (dx, dy, spatial_weight) in spatial.iter() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || nx >= width || ny < 0 || ny >= height {
+                    continue;
+                }
+                let j = ((ny * width + nx) * 4) as usize;
+                let distance: i32 = (0..3).map(|c| (input[i + c] as i32 - input[j + c] as i32).pow(2)).sum();
+                let weight = spatial_weight * range[distance as usize];
+                for (channel, total) in totals.iter_mut().enumerate() {
+                    *total += input[j + channel] as f32 * weight;
+                }
+                weights += weight;
+            }
+
+            for (channel, total) in totals.iter().enumerate() {
+                photon_image.raw_pixels[i + channel] = num::clamp((total / weights).round(), 0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+/// Guided filter, using each channel as its own guide. An edge-preserving smoothing filter that runs
+/// in constant time per pixel regardless of radius, and avoids the gradient reversal artifacts of the bilateral filter.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `radius` - The radius of the square window, in pixels.
+/// * `epsilon` - The regularisation, on a 0 to 1 channel scale. Must be greater than 0. Areas whose variance is
+/// well below this are smoothed, while edges with a variance well above it are kept. 0.01 is a good starting point.
+///
+/// # Example
+///
+/// ```
+/// // For example, to smooth an image while keeping strong edges:
+/// use photon::denoise::guided_filter;
+/// guided_filter(&mut img, 8, 0.01);
+/// ```
+#[wasm_bindgen]
+pub fn guided_filter(photon_image: &mut PhotonImage, radius: u32, epsilon: f32) {
+    // Flat windows have no variance, so without regularisation their linear model would be 0 / 0
+    if epsilon <= 0.0 || epsilon.is_nan() {
+        panic!("The guided filter's epsilon must be greater than 0, but was {}", epsilon);
+    }
+    let width = photon_image.width as usize;
+    let height = photon_image.height as usize;
+    let r = radius as usize;
+
+    for channel in 0..3 {
+        let guide: Vec<f32> = photon_image.raw_pixels.chunks(4).map(|px| px[channel] as f32 / 255.0).collect();
+        let squares: Vec<f32> = guide.iter().map(|v| v * v).collect();
+
+        let mean = box_means(&guide, width, height, r);
+        let mean_sq = box_means(&squares, width, height, r);
+
+        // Fit a linear model q = a * I + b in each window
+        let mut a = Vec::with_capacity(guide.len());
+        let mut b = Vec::with_capacity(guide.len());
+        for i in 0..guide.len() {
+            let variance = (mean_sq[i] - mean[i] * mean[i]).max(0.0);
+            let ai = variance / (variance + epsilon);
+            a.push(ai);
+            b.push(mean[i] - ai * mean[i]);
+        }
+
+        // Average the models of every window that covers each pixel
+        let mean_a = box_means(&a, width, height, r);
+        let mean_b = box_means(&b, width, height, r);
+
+        for (i, px) in photon_image.raw_pixels.chunks_mut(4).enumerate() {
+            let q = mean_a[i] * guide[i] + mean_b[i];
+            px[channel] = num::clamp((q * 255.0).round(), 0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Fast non-local means denoising. Each pixel is replaced by a weighted average of pixels in its
+/// search window, weighted by how similar the patches around them are. This removes noise while
+/// keeping repeated textures and fine detail better than local filters.
+///
+/// Patch distances for each search offset are computed with summed-area tables, so the running time
+/// doesn't depend on the patch size.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `h` - The filtering strength, on the 0 to 255 channel scale. Set this to about the standard deviation of the noise.
+/// * `patch_radius` - The radius of the patches being compared, in pixels. 1 to 3 is typical.
+/// * `search_radius` - The radius of the window searched for similar patches, in pixels. 5 to 10 is typical.
+///
+/// # Example
+///
+/// ```
+/// // For example, to denoise a noisy phone photo:
+/// use photon::denoise::non_local_means;
+/// non_local_means(&mut img, 10.0, 2, 7);
+/// ```
+#[wasm_bindgen]
+pub fn non_local_means(photon_image: &mut PhotonImage, h: f32, patch_radius: u32, search_radius: u32) {
+    if h <= 0.0 {
+        panic!("The filtering strength must be greater than 0");
+    }
+    let width = photon_image.width as usize;
+    let height = photon_image.height as usize;
+    let s = search_radius as i32;
+    let input = &photon_image.raw_pixels;
+
+    let mut totals = vec![[0.0f32; 3]; width * height];
+    let mut weights = vec![0.0f32; width * height];
+    let mut differences = vec![0.0f32; width * height];
+
+    for dy in -s..=s {
+        for dx in -s..=s {
+            // Squared colour difference between every pixel and the pixel at this offset
+            for y in 0..height {
+                for x in 0..width {
+                    let nx = num::clamp(x as i32 + dx, 0, width as i32 - 1) as usize;
+                    let ny = num::clamp(y as i32 + dy, 0, height as i32 - 1) as usize;
+                    let (i, j) = ((y * width + x) * 4, (ny * width + nx) * 4);
+                    differences[y * width + x] = (0..3).map(|c| (input[i + c] as f32 - input[j + c] as f32).powi(2)).sum();
+                }
+            }
+
+            let (sums, areas) = box_sums(&differences, width, height, patch_radius as usize);
+            for y in 0..height {
+                for x in 0..width {
+                    let nx = x as i32 + dx;
+                    let ny = y as i32 + dy;
+                    if nx < 0 || nx >= width as i32 || ny < 0 || ny >= height as i32 {
+                        continue;
+                    }
+                    let i = y * width + x;
+                    let j = (ny as usize * width + nx as usize) * 4;
+                    // The mean squared difference per channel value across the patch
+                    let distance = sums[i] / (areas[i] * 3.0);
+                    let weight = (-distance / (h * h)).exp();
+                    for (channel, total) in totals[i].iter_mut().enumerate() {
+                        *total += input[j + channel] as f32 * weight;
+                    }
+                    weights[i] += weight;
+                }
+            }
+        }
+    }
+
+    for (i, px) in photon_image.raw_pixels.chunks_mut(4).enumerate() {
+        for (channel, total) in totals[i].iter().enumerate() {
+            px[channel] = num::clamp((total / weights[i]).round(), 0.0, 255.0) as u8;
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Geometric distortions, such as swirl, pinch, waves, ripples, fisheye and displacement maps.
+//!
+//! Every effect is built on `remap`, which fills each output pixel by sampling the source image
+//! at a mapped position, with bilinear interpolation.
+
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+use crate::PhotonImage;
+
+/// Sample an image at a fractional position using bilinear interpolation.
+/// Positions outside the image take the colour of the nearest edge pixel.
+pub fn sample_bilinear(photon_image: &PhotonImage, x: f32, y: f32) -> [u8; 4] {
+    let width = photon_image.width as i64;
+    let height = photon_image.height as i64;
+
+    // Pixel centres are at half-integer positions
+    let (x, y) = (x - 0.5, y - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+
+    let pixel = |px: i64, py: i64| -> &[u8] {
+        let px = num::clamp(px, 0, width - 1);
+        let py = num::clamp(py, 0, height - 1);
+        let i = ((py * width + px) * 4) as usize;
+        &photon_image.raw_pixels[i..i + 4]
+    };
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let (p00, p10, p01, p11) = (pixel(x0, y0), pixel(x0 + 1, y0), pixel(x0, y0 + 1), pixel(x0 + 1, y0 + 1));
+
+    let mut result = [0u8; 4];
+    for (channel, value) in result.iter_mut().enumerate() {
+        let top = p00[channel] as f32 * (1.0 - fx) + p10[channel] as f32 * fx;
+        let bottom = p01[channel] as f32 * (1.0 - fx) + p11[channel] as f32 * fx;
+        *value = num::clamp((top * (1.0 - fy) + bottom * fy).round(), 0.0, 255.0) as u8;
+    }
+    result
+}
+
+/// Distort an image with a coordinate mapping.
+///
+/// For each output pixel, `mapping` is called with the position of the pixel's centre and returns
+/// the position in the source image to sample from. Positions are in pixels, from (0, 0) at the top-left corner.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `mapping` - A function from output positions to source positions.
+///
+/// # Example
+///
+/// ```
+/// // For example, to mirror the left half of an image onto the right half:
+/// use photon::distort::remap;
+/// let width = img.get_width() as f32;
+/// remap(&mut img, |x, y| (if x < width / 2.0 { x } else { width - x }, y));
+/// ```
+pub fn remap<F>(photon_image: &mut PhotonImage, mapping: F) where F: Fn(f32, f32) -> (f32, f32) {
+    let width = photon_image.width;
+    let mut raw_pixels = Vec::with_capacity(photon_image.raw_pixels.len());
+
+    for y in 0..photon_image.height {
+        for x in 0..width {
+            let (sx, sy) = mapping(x as f32 + 0.5, y as f32 + 0.5);
+            raw_pixels.extend_from_slice(&sample_bilinear(photon_image, sx, sy));
+        }
+    }
+    photon_image.raw_pixels = raw_pixels;
+}
+
+/// Distort an image radially around its centre.
+///
+/// `mapping` receives each pixel's distance from the centre, as a fraction of `radius` (given as a fraction
+/// of half the image's shorter side), along with its angle, and returns the distance and angle to sample from.
+/// Pixels beyond the radius are left untouched.
+fn remap_radial<F>(photon_image: &mut PhotonImage, radius: f32, mapping: F) where F: Fn(f32, f32) -> (f32, f32) {
+    let cx = photon_image.width as f32 / 2.0;
+    let cy = photon_image.height as f32 / 2.0;
+    let radius = radius * cx.min(cy);
+    if radius <= 0.0 {
+        return;
+    }
+
+    remap(photon_image, |x, y| {
+        let (dx, dy) = (x - cx, y - cy);
+        let distance = (dx * dx + dy * dy).sqrt() / radius;
+        if distance >= 1.0 {
+            return (x, y);
+        }
+        let (source_distance, angle) = mapping(distance, dy.atan2(dx));
+        (cx + source_distance * radius * angle.cos(), cy + source_distance * radius * angle.sin())
+    });
+}
+
+/// Swirl an image around its centre. The rotation is strongest at the centre and fades out towards the radius.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `angle` - The rotation at the centre, in degrees. Negative values swirl anticlockwise.
+/// * `radius` - The radius of the swirl, as a fraction of half the image's shorter side.
+///
+/// # Example
+///
+/// ```
+/// use photon::distort::swirl;
+/// swirl(&mut img, 180.0, 1.0);
+/// ```
+#[wasm_bindgen]
+pub fn swirl(photon_image: &mut PhotonImage, angle: f32, radius: f32) {
+    let angle = angle.to_radians();
+    remap_radial(photon_image, radius, |distance, theta| {
+        (distance, theta - angle * (1.0 - distance).powi(2))
+    });
+}
+
+/// Pinch or bulge an image around its centre.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `amount` - From -1 to 1. Positive values pinch the centre inwards, negative values bulge it outwards.
+/// * `radius` - The radius of the effect, as a fraction of half the image's shorter side.
+///
+/// # Example
+///
+/// ```
+/// // For example, to bulge the centre of an image:
+/// use photon::distort::pinch;
+/// pinch(&mut img, -0.5, 0.8);
+/// ```
+#[wasm_bindgen]
+pub fn pinch(photon_image: &mut PhotonImage, amount: f32, radius: f32) {
+    let exponent = 2.0f32.powf(-num::clamp(amount, -1.0, 1.0));
+    remap_radial(photon_image, radius, |distance, theta| (distance.powf(exponent), theta));
+}
+
+/// Distort an image with sine waves, shifting rows horizontally and columns vertically.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `amplitude` - The largest shift, in pixels.
+/// * `wavelength` - The distance between wave peaks, in pixels.
+///
+/// # Example
+///
+/// ```
+/// use photon::distort::wave;
+/// wave(&mut img, 10.0, 80.0);
+/// ```
+#[wasm_bindgen]
+pub fn wave(photon_image: &mut PhotonImage, amplitude: f32, wavelength: f32) {
+    if wavelength <= 0.0 {
+        panic!("The wavelength must be greater than 0");
+    }
+    remap(photon_image, |x, y| {
+        (x + amplitude * (2.0 * PI * y / wavelength).sin(), y + amplitude * (2.0 * PI * x / wavelength).sin())
+    });
+}
+
+/// Add ripples spreading out from the centre of an image, like a stone dropped in water.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `amplitude` - The largest shift, in pixels.
+/// * `wavelength` - The distance between ripples, in pixels.
+///
+/// # Example
+///
+/// ```
+/// use photon::distort::ripple;
+/// ripple(&mut img, 6.0, 40.0);
+/// ```
+#[wasm_bindgen]
+pub fn ripple(photon_image: &mut PhotonImage, amplitude: f32, wavelength: f32) {
+    if wavelength <= 0.0 {
+        panic!("The wavelength must be greater than 0");
+    }
+    let cx = photon_image.width as f32 / 2.0;
+    let cy = photon_image.height as f32 / 2.0;
+    remap(photon_image, |x, y| {
+        let (dx, dy) = (x - cx, y - cy);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance == 0.0 {
+            return (x, y);
+        }
+        let shift = amplitude * (2.0 * PI * distance / wavelength).sin();
+        (x + dx / distance * shift, y + dy / distance * shift)
+    });
+}
+
+/// Apply fisheye (barrel) or pincushion lens distortion. The corners of the image stay in place.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `strength` - From -1 to 1. Positive values give barrel distortion, bowing straight lines outwards
+/// like a fisheye lens. Negative values give pincushion distortion, bowing them inwards.
+///
+/// # Example
+///
+/// ```
+/// use photon::distort::fisheye;
+/// fisheye(&mut img, 0.6);
+/// ```
+#[wasm_bindgen]
+pub fn fisheye(photon_image: &mut PhotonImage, strength: f32) {
+    let strength = num::clamp(strength, -1.0, 1.0);
+    // Below -1/3 the mapping would fold back on itself
+    let k = if strength < 0.0 { strength / 3.0 } else { strength };
+    let cx = photon_image.width as f32 / 2.0;
+    let cy = photon_image.height as f32 / 2.0;
+    let half_diagonal = (cx * cx + cy * cy).sqrt();
+
+    remap(photon_image, |x, y| {
+        let (dx, dy) = (x - cx, y - cy);
+        let r = (dx * dx + dy * dy).sqrt() / half_diagonal;
+        let scale = (1.0 + k * r * r) / (1.0 + k);
+        (cx + dx * scale, cy + dy * scale)
+    });
+}
+
+/// Unwrap an image from polar to rectangular coordinates.
+///
+/// The angle around the centre of the source image becomes the horizontal axis, and the distance from
+/// the centre becomes the vertical axis, so circles around the centre become horizontal lines.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+///
+/// # Example
+///
+/// ```
+/// use photon::distort::polar_to_rectangular;
+/// polar_to_rectangular(&mut img);
+/// ```
+#[wasm_bindgen]
+pub fn polar_to_rectangular(photon_image: &mut PhotonImage) {
+    let (width, height) = (photon_image.width as f32, photon_image.height as f32);
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let max_radius = cx.min(cy);
+
+    remap(photon_image, |x, y| {
+        let angle = x / width * 2.0 * PI;
+        let radius = y / height * max_radius;
+        (cx + radius * angle.cos(), cy + radius * angle.sin())
+    });
+}
+
+/// Wrap an image from rectangular to polar coordinates, the inverse of `polar_to_rectangular`.
+///
+/// The horizontal axis of the source image becomes the angle around the centre, and the vertical axis
+/// becomes the distance from the centre, so horizontal lines become circles. This creates "tiny planet" effects from panoramas.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+///
+/// # Example
+///
+/// ```
+/// use photon::distort::rectangular_to_polar;
+/// rectangular_to_polar(&mut img);
+/// ```
+#[wasm_bindgen]
+pub fn rectangular_to_polar(photon_image: &mut PhotonImage) {
+    let (width, height) = (photon_image.width as f32, photon_image.height as f32);
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let max_radius = cx.min(cy);
+
+    remap(photon_image, |x, y| {
+        let (dx, dy) = (x - cx, y - cy);
+        let mut angle = dy.atan2(dx);
+        if angle < 0.0 {
+            angle += 2.0 * PI;
+        }
+        (angle / (2.0 * PI) * width, (dx * dx + dy * dy).sqrt() / max_radius * height)
+    });
+}
+
+/// Distort an image using a displacement map.
+///
+/// The red channel of the map shifts pixels horizontally and the green channel shifts them vertically.
+/// A value of 128 means no shift, 0 shifts by `-scale`, and 255 shifts by almost `+scale`.
+/// Greyscale maps, such as those from the `textures` module, shift both directions equally.
+/// If the map is a different size to the image, it is stretched to fit.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `map` - The displacement map.
+/// * `scale_x` - The largest horizontal shift, in pixels.
+/// * `scale_y` - The largest vertical shift, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to distort an image as if seen through frosted glass:
+/// use photon::distort::displacement_map;
+/// use photon::textures::perlin_noise;
+/// let map = perlin_noise(img.get_width(), img.get_height(), 8.0, 1);
+/// displacement_map(&mut img, &map, 6.0, 6.0);
+/// ```
+#[wasm_bindgen]
+pub fn displacement_map(photon_image: &mut PhotonImage, map: &PhotonImage, scale_x: f32, scale_y: f32) {
+    let map_scale_x = map.width as f32 / photon_image.width as f32;
+    let map_scale_y = map.height as f32 / photon_image.height as f32;
+
+    remap(photon_image, |x, y| {
+        let shift = sample_bilinear(map, x * map_scale_x, y * map_scale_y);
+        (x + (shift[0] as f32 - 128.0) / 128.0 * scale_x, y + (shift[1] as f32 - 128.0) / 128.0 * scale_y)
+    });
+}
@@ -0,0 +1,250 @@
+//! Error-diffusion and ordered dithering, to black and white or to a palette.
+
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use crate::channels::lab_from_rgb;
+use crate::{PhotonImage, Rgb};
+
+/// Dithering algorithms.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DitherAlgorithm {
+    FloydSteinberg = 1,
+    Atkinson = 2,
+    JarvisJudiceNinke = 3,
+    Sierra = 4,
+    Bayer2 = 5,
+    Bayer4 = 6,
+    Bayer8 = 7,
+}
+
+/// An error-diffusion kernel: (dx, dy, weight) entries, and the divisor for the weights.
+type DiffusionKernel = (&'static [(isize, isize, f32)], f32);
+
+const FLOYD_STEINBERG: DiffusionKernel = (&[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)], 16.0);
+
+// Atkinson only diffuses 6/8 of the error, which keeps highlights and shadows clean
+const ATKINSON: DiffusionKernel = (
+    &[(1, 0, 1.0), (2, 0, 1.0), (-1, 1, 1.0), (0, 1, 1.0), (1, 1, 1.0), (0, 2, 1.0)],
+    8.0,
+);
+
+const JARVIS_JUDICE_NINKE: DiffusionKernel = (
+    &[
+        (1, 0, 7.0), (2, 0, 5.0),
+        (-2, 1, 3.0), (-1, 1, 5.0), (0, 1, 7.0), (1, 1, 5.0), (2, 1, 3.0),
+        (-2, 2, 1.0), (-1, 2, 3.0), (0, 2, 5.0), (1, 2, 3.0), (2, 2, 1.0),
+    ],
+    48.0,
+);
+
+const SIERRA: DiffusionKernel = (
+    &[
+        (1, 0, 5.0), (2, 0, 3.0),
+        (-2, 1, 2.0), (-1, 1, 4.0), (0, 1, 5.0), (1, 1, 4.0), (2, 1, 2.0),
+        (-1, 2, 2.0), (0, 2, 3.0), (1, 2, 2.0),
+    ],
+    32.0,
+);
+
+/// Build an n x n Bayer threshold matrix, with values normalised to the range -0.5 to 0.5.
+fn bayer_matrix(n: usize) -> Vec<f32> {
+    let mut matrix = vec![0usize];
+    let mut size = 1;
+    while size < n {
+        let mut next = vec![0; size * size * 4];
+        for y in 0..size {
+            for x in 0..size {
+                let v = matrix[y * size + x] * 4;
+                next[y * size * 2 + x] = v;
+                next[y * size * 2 + x + size] = v + 2;
+                next[(y + size) * size * 2 + x] = v + 3;
+                next[(y + size) * size * 2 + x + size] = v + 1;
+            }
+        }
+        matrix = next;
+        size *= 2;
+    }
+    matrix.iter().map(|v| (*v as f32 + 0.5) / (n * n) as f32 - 0.5).collect()
+}
+
+/// A fixed set of colours, with a cache of the closest palette colour for each RGB value seen.
+struct Palette {
+    colours: Vec<Rgb>,
+    labs: Vec<[f32; 3]>,
+    cache: HashMap<[u8; 3], usize>,
+}
+
+impl Palette {
+    fn new(colours: &[Rgb]) -> Palette {
+        let labs = colours.iter()
+            .map(|c| {
+                let lab = lab_from_rgb(c.r, c.g, c.b);
+                [lab.l, lab.a, lab.b]
+            })
+            .collect();
+        Palette { colours: colours.to_vec(), labs, cache: HashMap::new() }
+    }
+
+    /// Find the perceptually closest palette colour, measured in the l*a*b colour space.
+    fn nearest(&mut self, rgb: [u8; 3]) -> [u8; 3] {
+        let labs = &self.labs;
+        let index = *self.cache.entry(rgb).or_insert_with(|| {
+            let lab = lab_from_rgb(rgb[0], rgb[1], rgb[2]);
+            let mut nearest = 0;
+            let mut nearest_distance = f32::MAX;
+            for (i, c) in labs.iter().enumerate() {
+                let distance = (c[0] - lab.l).powi(2) + (c[1] - lab.a).powi(2) + (c[2] - lab.b).powi(2);
+                if distance < nearest_distance {
+                    nearest = i;
+                    nearest_distance = distance;
+                }
+            }
+            nearest
+        });
+        let colour = self.colours[index];
+        [colour.r, colour.g, colour.b]
+    }
+
+    /// The typical distance between neighbouring palette levels on each channel, used to scale ordered dithering.
+    fn spread(&self) -> f32 {
+        255.0 / (self.colours.len() as f32).cbrt().max(1.0)
+    }
+}
+
+/// Dither an image in place, using `quantize` to map each (possibly error-adjusted) colour to an output colour.
+fn dither_with<F>(photon_image: &mut PhotonImage, algorithm: DitherAlgorithm, spread: f32, mut quantize: F)
+    where F: FnMut([u8; 3]) -> [u8; 3]
+{
+    let width = photon_image.width as usize;
+    let height = photon_image.height as usize;
+
+    let kernel = match algorithm {
+        DitherAlgorithm::FloydSteinberg => FLOYD_STEINBERG,
+        DitherAlgorithm::Atkinson => ATKINSON,
+        DitherAlgorithm::JarvisJudiceNinke => JARVIS_JUDICE_NINKE,
+        DitherAlgorithm::Sierra => SIERRA,
+        DitherAlgorithm::Bayer2 | DitherAlgorithm::Bayer4 | DitherAlgorithm::Bayer8 => {
+            let n = match algorithm {
+                DitherAlgorithm::Bayer2 => 2,
+                DitherAlgorithm::Bayer4 => 4,
+                _ => 8,
+            };
+            let matrix = bayer_matrix(n);
+            for y in 0..height {
+                for x in 0..width {
+                    let i = (y * width + x) * 4;
+                    let offset = matrix[(y % n) * n + x % n] * spread;
+                    let px = &mut photon_image.raw_pixels[i..i + 3];
+                    let adjusted = [
+                        num::clamp(px[0] as f32 + offset, 0.0, 255.0).round() as u8,
+                        num::clamp(px[1] as f32 + offset, 0.0, 255.0).round() as u8,
+                        num::clamp(px[2] as f32 + offset, 0.0, 255.0).round() as u8,
+                    ];
+                    px.copy_from_slice(&quantize(adjusted));
+                }
+            }
+            return;
+        }
+    };
+
+    let (offsets, divisor) = kernel;
+    // Errors are accumulated in a float buffer so they aren't clipped before being diffused
+    let mut buffer: Vec<f32> = photon_image.raw_pixels.iter().map(|v| *v as f32).collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) * 4;
+            let old = [
+                num::clamp(buffer[i], 0.0, 255.0),
+                num::clamp(buffer[i + 1], 0.0, 255.0),
+                num::clamp(buffer[i + 2], 0.0, 255.0),
+            ];
+            let new = quantize([old[0].round() as u8, old[1].round() as u8, old[2].round() as u8]);
+
+            for channel in 0..3 {
+                photon_image.raw_pixels[i + channel] = new[channel];
+                let error = old[channel] - new[channel] as f32;
+
+                for (dx, dy, weight) in offsets.iter() {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    if nx >= 0 && nx < width as isize && ny < height as isize {
+                        let j = (ny as usize * width + nx as usize) * 4 + channel;
+                        buffer[j] += error * weight / divisor;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Dither an image to pure black and white.
+///
+/// Each pixel is converted to its luma value before dithering, so the output only contains black and white pixels.
+/// The alpha channel is left untouched.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `algorithm` - The dithering algorithm to use. FloydSteinberg = 1, Atkinson = 2, JarvisJudiceNinke = 3,
+/// Sierra = 4, Bayer2 = 5, Bayer4 = 6, Bayer8 = 7.
+///
+/// # Example
+///
+/// ```
+/// // For example, to create an e-ink style preview using Atkinson dithering:
+/// use photon::dither::{dither, DitherAlgorithm};
+/// dither(&mut img, DitherAlgorithm::Atkinson);
+/// ```
+#[wasm_bindgen]
+pub fn dither(photon_image: &mut PhotonImage, algorithm: DitherAlgorithm) {
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        let luma = (px[0] as f32 * 0.2126 + px[1] as f32 * 0.7152 + px[2] as f32 * 0.0722).round() as u8;
+        px[0] = luma;
+        px[1] = luma;
+        px[2] = luma;
+    }
+
+    dither_with(photon_image, algorithm, 255.0, |rgb| {
+        if rgb[0] >= 128 { [255, 255, 255] } else { [0, 0, 0] }
+    });
+}
+
+/// Dither an image to an arbitrary palette of colours.
+///
+/// Each pixel is replaced by the perceptually closest colour in the palette, after the dithering
+/// algorithm has spread quantization error to its neighbours. The alpha channel is left untouched.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `algorithm` - The dithering algorithm to use. FloydSteinberg = 1, Atkinson = 2, JarvisJudiceNinke = 3,
+/// Sierra = 4, Bayer2 = 5, Bayer4 = 6, Bayer8 = 7.
+/// * `palette` - The colours that may appear in the output. An empty palette leaves the image unchanged.
+///
+/// # Example
+///
+/// ```
+/// // For example, to dither an image to a four-colour retro handheld palette:
+/// use photon::dither::{dither_to_palette, DitherAlgorithm};
+/// use photon::Rgb;
+/// let palette = vec![Rgb::new(15, 56, 15), Rgb::new(48, 98, 48), Rgb::new(139, 172, 15), Rgb::new(155, 188, 15)];
+/// dither_to_palette(&mut img, DitherAlgorithm::Bayer4, palette);
+/// ```
+#[wasm_bindgen]
+pub fn dither_to_palette(photon_image: &mut PhotonImage, algorithm: DitherAlgorithm, palette: Vec<Rgb>) {
+    if palette.is_empty() {
+        return;
+    }
+    let mut palette = Palette::new(&palette);
+    let spread = palette.spread();
+    dither_with(photon_image, algorithm, spread, |rgb| palette.nearest(rgb));
+}
+
+/// Replace every pixel with the perceptually closest colour in a palette, without dithering.
+pub(crate) fn map_to_palette(photon_image: &mut PhotonImage, palette: &[Rgb]) {
+    let mut palette = Palette::new(palette);
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        let colour = palette.nearest([px[0], px[1], px[2]]);
+        px[..3].copy_from_slice(&colour);
+    }
+}
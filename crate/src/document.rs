@@ -0,0 +1,423 @@
+//! Documents made of layers, for non-destructive editing.
+//!
+//! A `Document` holds an ordered stack of layers, from the bottom up. Image layers hold pixels, and
+//! adjustment layers hold a `FilterPipeline` that applies to everything beneath them. Layers are only
+//! combined when the document is flattened, so any layer can be changed, hidden or removed at any time.
+//! Documents can be saved to and loaded from JSON, for undo history or project files.
+
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use wasm_bindgen::prelude::*;
+use crate::filters::FilterPipeline;
+use crate::mask::mask_weight;
+use crate::multiple::{blend_pixels, BlendMode};
+use crate::PhotonImage;
+
+/// Errors from creating, editing, saving or loading a `Document`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DocumentError {
+    /// The JSON couldn't be read or written.
+    Json(String),
+    /// The document's width and height are too large for its pixels to fit in memory.
+    InvalidSize,
+    /// The image of the named layer has the wrong number of pixels for its width and height.
+    InvalidImage(String),
+    /// The mask of the named layer isn't the same size as the document, or has the wrong number of pixels.
+    InvalidMask(String),
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DocumentError::Json(error) => write!(f, "Invalid document JSON: {}", error),
+            DocumentError::InvalidSize => write!(f, "The document is too large"),
+            DocumentError::InvalidImage(name) => write!(f, "The image of layer '{}' has the wrong number of pixels", name),
+            DocumentError::InvalidMask(name) => write!(f, "The mask of layer '{}' doesn't match the size of the document", name),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+impl From<DocumentError> for JsValue {
+    fn from(error: DocumentError) -> JsValue {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+/// Get the number of bytes of pixels an image of a given size needs, or `None` if it overflows.
+fn pixels_len(width: u32, height: u32) -> Option<usize> {
+    (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(4))
+}
+
+/// Check that an image's pixels match its width and height.
+fn is_valid_image(image: &PhotonImage) -> bool {
+    pixels_len(image.width, image.height) == Some(image.raw_pixels.len())
+}
+
+/// The contents of a layer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum LayerContent {
+    /// Pixels, positioned in the document by the layer's offset.
+    Image(PhotonImage),
+    /// An adjustment to the layers beneath.
+    Adjustment(FilterPipeline),
+}
+
+/// A layer of a `Document`.
+///
+/// # Example
+///
+/// ```
+/// // For example, to create a half-transparent layer that multiplies a texture onto the layers beneath:
+/// use photon::document::Layer;
+/// use photon::multiple::BlendMode;
+/// let mut layer = Layer::image("Paper texture", &texture, 0, 0);
+/// layer.set_opacity(0.5);
+/// layer.set_blend_mode(BlendMode::Multiply);
+/// ```
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Layer {
+    name: String,
+    content: LayerContent,
+    x: i32,
+    y: i32,
+    opacity: f32,
+    blend_mode: BlendMode,
+    visible: bool,
+    mask: Option<PhotonImage>,
+}
+
+#[wasm_bindgen]
+impl Layer {
+    /// Create an image layer, with its top-left corner at (`x`, `y`) in the document.
+    pub fn image(name: &str, photon_image: &PhotonImage, x: i32, y: i32) -> Layer {
+        Layer::new(name, LayerContent::Image(photon_image.clone()), x, y)
+    }
+
+    /// Create an adjustment layer, which applies a pipeline to the layers beneath it.
+    pub fn adjustment(name: &str, pipeline: &FilterPipeline) -> Layer {
+        Layer::new(name, LayerContent::Adjustment(pipeline.clone()), 0, 0)
+    }
+
+    /// Get the name of the layer.
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Set the name of the layer.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Get whether this is an adjustment layer, rather than an image layer.
+    pub fn is_adjustment(&self) -> bool {
+        match self.content {
+            LayerContent::Adjustment(_) => true,
+            LayerContent::Image(_) => false,
+        }
+    }
+
+    /// Get the x-coordinate of the layer's top-left corner in the document.
+    pub fn get_x(&self) -> i32 {
+        self.x
+    }
+
+    /// Get the y-coordinate of the layer's top-left corner in the document.
+    pub fn get_y(&self) -> i32 {
+        self.y
+    }
+
+    /// Move the layer's top-left corner to (`x`, `y`) in the document. Adjustment layers always cover the whole document.
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// Get the opacity of the layer, from 0 to 1.
+    pub fn get_opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Set the opacity of the layer, from 0 (invisible) to 1 (opaque).
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = num::clamp(opacity, 0.0, 1.0);
+    }
+
+    /// Get the blend mode of the layer.
+    pub fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set how the layer is blended with the layers beneath it.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Get whether the layer is visible.
+    pub fn get_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Show or hide the layer.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Get whether the layer has a mask.
+    pub fn has_mask(&self) -> bool {
+        self.mask.is_some()
+    }
+
+    /// Set a greyscale mask, the same size as the document, that limits where the layer shows.
+    /// See `mask::blend_with_mask` for how masks are used.
+    pub fn set_mask(&mut self, mask: &PhotonImage) {
+        self.mask = Some(mask.clone());
+    }
+
+    /// Remove the layer's mask.
+    pub fn clear_mask(&mut self) {
+        self.mask = None;
+    }
+}
+
+impl Layer {
+    fn new(name: &str, content: LayerContent, x: i32, y: i32) -> Layer {
+        Layer { name: name.to_string(), content, x, y, opacity: 1.0, blend_mode: BlendMode::Normal, visible: true, mask: None }
+    }
+
+    /// Get the contents of the layer.
+    pub fn content(&self) -> &LayerContent {
+        &self.content
+    }
+
+    /// Get the contents of the layer, to edit them.
+    pub fn content_mut(&mut self) -> &mut LayerContent {
+        &mut self.content
+    }
+
+    /// The weight of the layer at a position in the document, from its opacity and mask.
+    fn weight(&self, i: usize) -> f32 {
+        match &self.mask {
+            Some(mask) => self.opacity * mask_weight(&mask.raw_pixels[i * 4..i * 4 + 4]),
+            None => self.opacity,
+        }
+    }
+
+    /// Blend the layer onto a flattened image of the layers beneath it.
+    fn composite(&self, canvas: &mut PhotonImage) {
+        let (width, height) = (canvas.width as i32, canvas.height as i32);
+        if let Some(mask) = &self.mask {
+            if mask.width != canvas.width || mask.height != canvas.height {
+                panic!("The mask of layer '{}' must be the same size as the document", self.name);
+            }
+        }
+
+        let blend_onto = |canvas: &mut PhotonImage, i: usize, px: &[u8]| {
+            let alpha = (px[3] as f32 * self.weight(i)).round() as u8;
+            let base = [canvas.raw_pixels[i * 4], canvas.raw_pixels[i * 4 + 1], canvas.raw_pixels[i * 4 + 2], canvas.raw_pixels[i * 4 + 3]];
+            let blended = blend_pixels(base, [px[0], px[1], px[2], alpha], self.blend_mode);
+            canvas.raw_pixels[i * 4..i * 4 + 4].copy_from_slice(&blended);
+        };
+
+        match &self.content {
+            LayerContent::Image(image) => {
+                // Only the part of the layer that overlaps the document is drawn
+                for ly in 0..image.height as i32 {
+                    let y = ly + self.y;
+                    if y < 0 || y >= height {
+                        continue;
+                    }
+                    for lx in 0..image.width as i32 {
+                        let x = lx + self.x;
+                        if x < 0 || x >= width {
+                            continue;
+                        }
+                        let j = ((ly * image.width as i32 + lx) * 4) as usize;
+                        blend_onto(canvas, (y * width + x) as usize, &image.raw_pixels[j..j + 4]);
+                    }
+                }
+            }
+            LayerContent::Adjustment(pipeline) => {
+                let mut adjusted = canvas.clone();
+                pipeline.apply(&mut adjusted);
+                for (i, px) in adjusted.raw_pixels.chunks(4).enumerate() {
+                    blend_onto(canvas, i, px);
+                }
+            }
+        }
+    }
+}
+
+/// A stack of layers that can be flattened into a single image.
+///
+/// # Example
+///
+/// ```
+/// // For example, to add a logo and a warm adjustment to a photo, then flatten the result:
+/// use photon::document::{Document, Layer};
+/// use photon::filters::FilterPipeline;
+/// use photon::Rgb;
+/// let mut document = Document::from_image(&photo)?;
+/// document.add_layer(&Layer::image("Logo", &logo, 20, 20))?;
+/// let mut warm = FilterPipeline::new();
+/// warm.tint(Rgb::new(255, 180, 100), 0.1);
+/// document.add_layer(&Layer::adjustment("Warm", &warm))?;
+/// let flattened = document.flatten();
+/// ```
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Document {
+    width: u32,
+    height: u32,
+    layers: Vec<Layer>,
+}
+
+#[wasm_bindgen]
+impl Document {
+    #[wasm_bindgen(constructor)]
+    /// Create an empty, transparent document. Returns an error if it's too large for its pixels to fit in memory.
+    pub fn new(width: u32, height: u32) -> Result<Document, DocumentError> {
+        if pixels_len(width, height).is_none() {
+            return Err(DocumentError::InvalidSize);
+        }
+        Ok(Document { width, height, layers: vec![] })
+    }
+
+    /// Create a document the size of an image, with the image as its only layer, named "Background".
+    pub fn from_image(photon_image: &PhotonImage) -> Result<Document, DocumentError> {
+        let mut document = Document::new(photon_image.width, photon_image.height)?;
+        document.add_layer(&Layer::image("Background", photon_image, 0, 0))?;
+        Ok(document)
+    }
+
+    /// Load a document saved with `to_json`. Returns an error if the JSON is invalid,
+    /// or if any layer's image or mask doesn't match its size.
+    pub fn from_json(json: &str) -> Result<Document, DocumentError> {
+        let document: Document = serde_json::from_str(json).map_err(|error| DocumentError::Json(error.to_string()))?;
+        if pixels_len(document.width, document.height).is_none() {
+            return Err(DocumentError::InvalidSize);
+        }
+        for layer in document.layers.iter() {
+            document.check_layer(layer)?;
+        }
+        Ok(document)
+    }
+
+    /// Save the document, including every layer's pixels, as JSON.
+    pub fn to_json(&self) -> Result<String, DocumentError> {
+        serde_json::to_string(self).map_err(|error| DocumentError::Json(error.to_string()))
+    }
+
+    /// Get the width of the document.
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the document.
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the number of layers.
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Add a copy of a layer on top of the other layers, and return its index.
+    /// Returns an error if the layer's image or mask doesn't match its size, or its mask isn't the size of the document.
+    pub fn add_layer(&mut self, layer: &Layer) -> Result<usize, DocumentError> {
+        self.check_layer(layer)?;
+        self.layers.push(layer.clone());
+        Ok(self.layers.len() - 1)
+    }
+
+    /// Insert a copy of a layer at an index, where 0 is the bottom of the stack.
+    /// Returns an error if the layer's image or mask doesn't match its size, or its mask isn't the size of the document.
+    pub fn insert_layer(&mut self, index: usize, layer: &Layer) -> Result<(), DocumentError> {
+        self.check_insert_index(index);
+        self.check_layer(layer)?;
+        self.layers.insert(index, layer.clone());
+        Ok(())
+    }
+
+    /// Get a copy of the layer at an index.
+    pub fn get_layer(&self, index: usize) -> Layer {
+        self.layers[self.check_index(index)].clone()
+    }
+
+    /// Replace the layer at an index with a copy of another layer.
+    /// Returns an error if the layer's image or mask doesn't match its size, or its mask isn't the size of the document.
+    pub fn set_layer(&mut self, index: usize, layer: &Layer) -> Result<(), DocumentError> {
+        let index = self.check_index(index);
+        self.check_layer(layer)?;
+        self.layers[index] = layer.clone();
+        Ok(())
+    }
+
+    /// Remove the layer at an index, and return it.
+    pub fn remove_layer(&mut self, index: usize) -> Layer {
+        let index = self.check_index(index);
+        self.layers.remove(index)
+    }
+
+    /// Move a layer to a new index in the stack.
+    pub fn move_layer(&mut self, from: usize, to: usize) {
+        let layer = self.remove_layer(from);
+        self.check_insert_index(to);
+        self.layers.insert(to, layer);
+    }
+
+    /// Flatten the visible layers into a single image, without changing the document.
+    pub fn flatten(&self) -> PhotonImage {
+        let len = pixels_len(self.width, self.height).expect("The document is too large");
+        let mut canvas = PhotonImage::new(vec![0; len], self.width, self.height);
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            layer.composite(&mut canvas);
+        }
+        canvas
+    }
+}
+
+impl Document {
+    fn check_index(&self, index: usize) -> usize {
+        if index >= self.layers.len() {
+            panic!("Layer index {} is out of range for a document with {} layers", index, self.layers.len());
+        }
+        index
+    }
+
+    fn check_insert_index(&self, index: usize) {
+        if index > self.layers.len() {
+            panic!("Layer index {} is out of range for a document with {} layers", index, self.layers.len());
+        }
+    }
+
+    /// Check that a layer's image and mask match their sizes, and its mask is the size of the document.
+    fn check_layer(&self, layer: &Layer) -> Result<(), DocumentError> {
+        if let LayerContent::Image(image) = &layer.content {
+            if !is_valid_image(image) {
+                return Err(DocumentError::InvalidImage(layer.name.clone()));
+            }
+        }
+        if let Some(mask) = &layer.mask {
+            if !is_valid_image(mask) || mask.width != self.width || mask.height != self.height {
+                return Err(DocumentError::InvalidMask(layer.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the layers, from the bottom of the stack up.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Get a layer, to edit it in place. Its image and mask must still match their sizes, and its mask
+    /// the size of the document, when the document is flattened.
+    pub fn layer_mut(&mut self, index: usize) -> &mut Layer {
+        let index = self.check_index(index);
+        &mut self.layers[index]
+    }
+}
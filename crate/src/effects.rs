@@ -9,6 +9,7 @@ use imageproc::rect::Rect;
 extern crate rusttype;
 use crate::{PhotonImage, Rgb};
 use crate::helpers;
+use crate::channels::ChannelMask;
 use wasm_bindgen::prelude::*;
 use image::{Rgba};
  
@@ -150,124 +151,231 @@ pub fn multiple_offsets(mut photon_image: &mut PhotonImage, offset: u32, channel
 }
 
 /// Halftoning effect.
-pub fn halftone(mut photon_image: PhotonImage) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
+///
+/// The image is split into 2x2 cells, and each cell is replaced by a pattern of black and white pixels
+/// that matches its average brightness. For error-diffusion and ordered dithering, see the `dither` module.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// # Example
+///
+/// ```
+/// // For example, to add a halftone effect to an image:
+/// use photon::effects;
+/// photon::effects::halftone(&mut img);
+/// ```
+#[wasm_bindgen]
+pub fn halftone(photon_image: &mut PhotonImage) {
+    let mut img = helpers::dyn_image_from_raw(photon_image);
     let (width, height) = img.dimensions();
-    
-
-    for x in (0..width).step_by(2 as usize) {
-        for y in (0..height).step_by(2 as usize) {
-
-            let mut px1 = img.get_pixel(x, y);
-            let mut px2 = img.get_pixel(x, y + 1);
-            let mut px3 = img.get_pixel(x + 1, y);
-            let mut px4 = img.get_pixel(x + 1, y + 1);
-
-            let gray1 = (px1[0] as f64 * 0.299) + (px1[1] as f64 * 0.587) + (px1[2] as f64 * 0.114);
-            let gray2 = (px2[0] as f64 * 0.299) + (px2[1] as f64 * 0.587) + (px2[2] as f64 * 0.114);
-            let gray3 = (px3[0] as f64 * 0.299) + (px3[1] as f64 * 0.587) + (px3[2] as f64 * 0.114);            
-            let gray4 = (px4[0] as f64 * 0.299) + (px4[1] as f64 * 0.587) + (px4[2] as f64 * 0.114);
-
-            let sat = (gray1 + gray2 + gray3 + gray4) / 4.0;
-
-            if sat > 200.0 {
-                px1.data[0] = 255;
-                px1.data[1] = 255;
-                px1.data[2] = 255;
-
-                px2.data[0] = 255;
-                px2.data[1] = 255;
-                px2.data[2] = 255;
-
-                px3.data[0] = 255;
-                px3.data[1] = 255;
-                px3.data[2] = 255;
-
-                px4.data[0] = 255;
-                px4.data[1] = 255;
-                px4.data[2] = 255;
 
+    // Cells are in the order (x, y), (x, y + 1), (x + 1, y), (x + 1, y + 1); true is white
+    let patterns = [
+        (200.0, [true, true, true, true]),
+        (159.0, [true, false, true, true]),
+        (95.0, [true, false, false, true]),
+        (32.0, [false, true, false, false]),
+    ];
+    let cell = [(0, 0), (0, 1), (1, 0), (1, 1)];
+
+    for x in (0..width).step_by(2) {
+        for y in (0..height).step_by(2) {
+            // Cells on the right and bottom edges of odd-sized images only cover the pixels that exist
+            let coords: Vec<(u32, u32)> = cell.iter()
+                .map(|(dx, dy)| (x + dx, y + dy))
+                .filter(|(cx, cy)| *cx < width && *cy < height)
+                .collect();
+
+            let sat = coords.iter()
+                .map(|(cx, cy)| {
+                    let px = img.get_pixel(*cx, *cy);
+                    (px[0] as f64 * 0.299) + (px[1] as f64 * 0.587) + (px[2] as f64 * 0.114)
+                })
+                .sum::<f64>() / coords.len() as f64;
+
+            let pattern = patterns.iter()
+                .find(|(threshold, _)| sat > *threshold)
+                .map_or([false; 4], |(_, pattern)| *pattern);
+
+            for (i, (dx, dy)) in cell.iter().enumerate() {
+                let (cx, cy) = (x + dx, y + dy);
+                if cx < width && cy < height {
+                    let mut px = img.get_pixel(cx, cy);
+                    let value = if pattern[i] { 255 } else { 0 };
+                    px.data[0] = value;
+                    px.data[1] = value;
+                    px.data[2] = value;
+                    img.put_pixel(cx, cy, px);
+                }
             }
+        }
+    }
+    let raw_pixels = img.raw_pixels();
+    photon_image.raw_pixels = raw_pixels;
+}
 
-            else if sat > 159.0 {
-                px1.data[0] = 255;
-                px1.data[1] = 255;
-                px1.data[2] = 255;
-
-                px2.data[0] = 0;
-                px2.data[1] = 0;
-                px2.data[2] = 0;
+/// Dot shapes for halftone screens.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HalftoneShape {
+    Round = 1,
+    Line = 2,
+    Square = 3,
+}
 
-                px3.data[0] = 255;
-                px3.data[1] = 255;
-                px3.data[2] = 255;
+impl HalftoneShape {
+    /// The spot function: how far a point, relative to the centre of its cell, is from the middle of the dot.
+    fn spot(self, u: f32, v: f32) -> f32 {
+        match self {
+            HalftoneShape::Round => (u * u + v * v).sqrt(),
+            HalftoneShape::Line => v.abs(),
+            HalftoneShape::Square => u.abs().max(v.abs()),
+        }
+    }
 
-                px4.data[0] = 255;
-                px4.data[1] = 255;
-                px4.data[2] = 255;
+    /// Spot function values sampled evenly over a cell and sorted, so that the value at the
+    /// `d` quantile is the threshold that inks a proportion `d` of the cell.
+    fn thresholds(self) -> Vec<f32> {
+        let samples = 64;
+        let mut thresholds = Vec::with_capacity(samples * samples);
+        for i in 0..samples {
+            for j in 0..samples {
+                let u = (i as f32 + 0.5) / samples as f32 - 0.5;
+                let v = (j as f32 + 0.5) / samples as f32 - 0.5;
+                thresholds.push(self.spot(u, v));
             }
+        }
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        thresholds
+    }
+}
 
-            else if sat > 95.0 {
-                px1.data[0] = 255;
-                px1.data[1] = 255;
-                px1.data[2] = 255;
-
-                px2.data[0] = 0;
-                px2.data[1] = 0;
-                px2.data[2] = 0;
-
-                px3.data[0] = 0;
-                px3.data[1] = 0;
-                px3.data[2] = 0;
-
-                px4.data[0] = 255;
-                px4.data[1] = 255;
-                px4.data[2] = 255;
+/// Screen a plane of ink densities (0 to 1) into anti-aliased dots, returning the ink coverage of each pixel.
+fn screen(plane: &[f32], width: u32, height: u32, cell_size: f32, angle: f32, shape: HalftoneShape) -> Vec<f32> {
+    let thresholds = shape.thresholds();
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let mut coverage = Vec::with_capacity(plane.len());
+
+    for y in 0..height {
+        for x in 0..width {
+            // Rotate the pixel centre into screen space, where each cell is one unit wide
+            let px = x as f32 + 0.5;
+            let py = y as f32 + 0.5;
+            let u = (px * cos + py * sin) / cell_size;
+            let v = (py * cos - px * sin) / cell_size;
+            let cu = u.floor() + 0.5;
+            let cv = v.floor() + 0.5;
+
+            // Each dot's size is set by the ink density at the centre of its cell
+            let sx = num::clamp(((cu * cos - cv * sin) * cell_size) as i64, 0, width as i64 - 1) as u32;
+            let sy = num::clamp(((cu * sin + cv * cos) * cell_size) as i64, 0, height as i64 - 1) as u32;
+            let density = plane[(sy * width + sx) as usize];
+
+            let ink = if density <= 0.0 {
+                0.0
             }
-
-            else if sat > 32.0 {
-                px1.data[0] = 0;
-                px1.data[1] = 0;
-                px1.data[2] = 0;
-
-                px2.data[0] = 255;
-                px2.data[0] = 255;
-                px2.data[0] = 255;
-
-                px3.data[0] = 0;
-                px3.data[1] = 0;
-                px3.data[2] = 0;                
-                
-                px4.data[0] = 0;
-                px4.data[1] = 0;
-                px4.data[2] = 0;
+            else if density >= 1.0 {
+                1.0
             }
-
             else {
-                px1.data[0] = 0;
-                px1.data[1] = 0;
-                px1.data[2] = 0;                
-                
-                px2.data[0] = 0;
-                px2.data[1] = 0;
-                px2.data[2] = 0;                
-                
-                px3.data[0] = 0;
-                px3.data[1] = 0;
-                px3.data[2] = 0;
+                let threshold = thresholds[(density * thresholds.len() as f32) as usize];
+                // Spot functions change by 1/cell_size per pixel, so this blends the dot edge over one pixel
+                num::clamp((threshold - shape.spot(u - cu, v - cv)) * cell_size + 0.5, 0.0, 1.0)
+            };
+            coverage.push(ink);
+        }
+    }
+    coverage
+}
 
-                px4.data[0] = 0;
-                px4.data[1] = 0;
-                px4.data[2] = 0;
-            }
+/// Newspaper-style halftone screen.
+///
+/// The image is converted to black dots on white paper, with the dots arranged on a grid rotated
+/// by `angle`. Each dot's size is proportional to the darkness of the image under it, and dot edges are anti-aliased.
+/// The alpha channel is left untouched.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// * `cell_size` - The distance between dot centres, in pixels. Must be at least 1.
+/// * `angle` - The screen angle, in degrees. 45 degrees is traditional for black and white.
+/// * `shape` - The dot shape. Round = 1, Line = 2, Square = 3.
+/// # Example
+///
+/// ```
+/// // For example, to add a halftone screen with 8 pixel round dots at 45 degrees:
+/// use photon::effects::{halftone_screen, HalftoneShape};
+/// halftone_screen(&mut img, 8.0, 45.0, HalftoneShape::Round);
+/// ```
+#[wasm_bindgen]
+pub fn halftone_screen(photon_image: &mut PhotonImage, cell_size: f32, angle: f32, shape: HalftoneShape) {
+    if cell_size < 1.0 {
+        panic!("The halftone cell size must be at least 1 pixel");
+    }
+    let plane: Vec<f32> = photon_image.raw_pixels.chunks(4)
+        .map(|px| 1.0 - (px[0] as f32 * 0.2126 + px[1] as f32 * 0.7152 + px[2] as f32 * 0.0722) / 255.0)
+        .collect();
 
+    let coverage = screen(&plane, photon_image.width, photon_image.height, cell_size, angle, shape);
 
-            img.put_pixel(x, y, px1);
-            // img.put_pixel(x, y + 1, px2);
-         }
+    for (px, ink) in photon_image.raw_pixels.chunks_mut(4).zip(coverage.iter()) {
+        let value = ((1.0 - ink) * 255.0).round() as u8;
+        px[0] = value;
+        px[1] = value;
+        px[2] = value;
+    }
+}
+
+/// CMYK halftone screen.
+///
+/// The image is separated into cyan, magenta, yellow and black inks, and each ink is screened at its classic
+/// angle (cyan 15, magenta 75, yellow 0 and black 45 degrees) to avoid moiré. The screened inks are then
+/// recombined on white paper, giving the look of a print under a magnifying glass. The alpha channel is left untouched.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// * `cell_size` - The distance between dot centres, in pixels. Must be at least 1.
+/// * `shape` - The dot shape. Round = 1, Line = 2, Square = 3.
+/// # Example
+///
+/// ```
+/// // For example, to create a pop-art poster effect with 10 pixel dots:
+/// use photon::effects::{halftone_cmyk, HalftoneShape};
+/// halftone_cmyk(&mut img, 10.0, HalftoneShape::Round);
+/// ```
+#[wasm_bindgen]
+pub fn halftone_cmyk(photon_image: &mut PhotonImage, cell_size: f32, shape: HalftoneShape) {
+    if cell_size < 1.0 {
+        panic!("The halftone cell size must be at least 1 pixel");
+    }
+    let len = photon_image.raw_pixels.len() / 4;
+    let mut planes = [vec![0.0; len], vec![0.0; len], vec![0.0; len], vec![0.0; len]];
+
+    for (i, px) in photon_image.raw_pixels.chunks(4).enumerate() {
+        let r = px[0] as f32 / 255.0;
+        let g = px[1] as f32 / 255.0;
+        let b = px[2] as f32 / 255.0;
+        let k = 1.0 - r.max(g).max(b);
+        if k < 1.0 {
+            planes[0][i] = (1.0 - r - k) / (1.0 - k);
+            planes[1][i] = (1.0 - g - k) / (1.0 - k);
+            planes[2][i] = (1.0 - b - k) / (1.0 - k);
+        }
+        planes[3][i] = k;
+    }
+
+    let angles = [15.0, 75.0, 0.0, 45.0];
+    let (width, height) = (photon_image.width, photon_image.height);
+    let inks: Vec<Vec<f32>> = planes.iter()
+        .zip(angles.iter())
+        .map(|(plane, angle)| screen(plane, width, height, cell_size, *angle, shape))
+        .collect();
+
+    for (i, px) in photon_image.raw_pixels.chunks_mut(4).enumerate() {
+        let paper = 255.0 * (1.0 - inks[3][i]);
+        px[0] = (paper * (1.0 - inks[0][i])).round() as u8;
+        px[1] = (paper * (1.0 - inks[1][i])).round() as u8;
+        px[2] = (paper * (1.0 - inks[2][i])).round() as u8;
     }
-    let raw_pixels = img.raw_pixels();
-    photon_image.raw_pixels = raw_pixels;
 }
 
 /// Reduces an image to the primary colours.
@@ -424,80 +532,129 @@ pub fn colorize(mut photon_image: &mut PhotonImage) {
 //     return photon_image;
 // }
 
-/// Applies a solarizing effect to an image.
-/// 
+/// Applies a solarizing effect to an image, inverting every channel value above a threshold.
+///
+/// Mimics the Sabattier effect of exposing a print to light during development: shadows are kept,
+/// while highlights are turned into their negative.
+///
 /// # Arguments
-/// * `img` - A PhotonImage that contains a view into the image.
+/// * `photon_image` - A PhotonImage.
+/// * `threshold` - Channel values above this are inverted. 128 gives the classic solarize curve.
+/// * `channels` - The channels to solarize.
 /// # Example
 ///
 /// ```
-/// // For example, to colorize an image of type `PhotonImage`:
-/// use photon::effects;
-/// photon::effects::solarize(img);
+/// // For example, to solarize every channel of an image:
+/// use photon::effects::solarize;
+/// use photon::channels::ChannelMask;
+/// solarize(&mut img, 128, ChannelMask::all());
 /// ```
 #[wasm_bindgen]
-pub fn solarize(mut photon_image: &mut PhotonImage) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
-    let (width, height) = img.dimensions();
+pub fn solarize(photon_image: &mut PhotonImage, threshold: u8, channels: ChannelMask) {
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        for (channel, value) in px.iter_mut().take(3).enumerate() {
+            if channels.contains(channel) && *value > threshold {
+                *value = 255 - *value;
+            }
+        }
+    }
+}
 
-    for x in 0..width {
-        for y in 0..height {
-            let mut px = img.get_pixel(x, y);
-            if 200 as i32 - px.data[0] as i32 > 0 {
-                px.data[0] = 200 - px.data[0];
+/// Invert a selection of an image's channels.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `channels` - The channels to invert.
+/// # Example
+///
+/// ```
+/// // For example, to invert only the Blue channel of an image:
+/// use photon::effects::invert_channels;
+/// use photon::channels::ChannelMask;
+/// invert_channels(&mut img, ChannelMask::new(false, false, true));
+/// ```
+#[wasm_bindgen]
+pub fn invert_channels(photon_image: &mut PhotonImage, channels: ChannelMask) {
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        for (channel, value) in px.iter_mut().take(3).enumerate() {
+            if channels.contains(channel) {
+                *value = 255 - *value;
             }
-            img.put_pixel(x, y, px);
         }
     }
-    let raw_pixels = img.raw_pixels();
-    photon_image.raw_pixels = raw_pixels;
 }
 
+/// Posterize an image, reducing each channel to a number of evenly spaced levels.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `levels_per_channel` - The number of levels each channel is reduced to, from 2 to 255.
+/// 2 levels per channel gives a palette of 8 colours.
+/// # Example
+///
+/// ```
+/// // For example, to posterize an image to 4 levels per channel:
+/// use photon::effects::posterize;
+/// posterize(&mut img, 4);
+/// ```
+#[wasm_bindgen]
+pub fn posterize(photon_image: &mut PhotonImage, levels_per_channel: u8) {
+    if levels_per_channel < 2 {
+        panic!("There must be at least 2 levels per channel");
+    }
+    let steps = (levels_per_channel - 1) as f32;
+    let levels: Vec<u8> = (0..=255)
+        .map(|v| {
+            let level = (v as f32 / 255.0 * steps).round();
+            (level * 255.0 / steps).round() as u8
+        })
+        .collect();
+
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        for value in px.iter_mut().take(3) {
+            *value = levels[*value as usize];
+        }
+    }
+}
 
-/// Increase the brightness of an image by a factor.
+
+/// Increase the brightness of an image by a fixed amount. Use `brightness` to darken images as well.
 /// 
 /// # Arguments
 /// * `img` - A PhotonImage that contains a view into the image.
-/// * `brightness` - A u8 to add to the brightness.
+/// * `brightness` - A u8 to add to every channel.
 /// # Example
 ///
 /// ```
 /// photon::effects::inc_brightness(img, 10);
 /// ```
 #[wasm_bindgen]
-pub fn inc_brightness(mut photon_image: &mut PhotonImage, brightness: u8) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
-    let (width, height) = img.dimensions();
-
-    for x in 0..width {
-        for y in 0..height {
-            let mut px = img.get_pixel(x, y);
-            if px.data[0] <= 255 - brightness {
-                px.data[0] += brightness;
-            }
-            else {
-                px.data[0] = 255;
-            }            
-            if px.data[1] <= 255 - brightness {
-                px.data[1] += brightness;
-            }
-
-            else {
-                px.data[1] = 255
-            }
-
-            if px.data[2] <= 255 - brightness {
-                px.data[2] += brightness;
-            }
-
-            else {
-                px.data[2] = 255
-            }
+pub fn inc_brightness(photon_image: &mut PhotonImage, brightness: u8) {
+    self::brightness(photon_image, brightness as f32 / 255.0);
+}
 
-            img.put_pixel(x, y, px);
+/// Brighten or darken an image, by adding the same offset to every channel.
+/// Channel values are clamped at 0 and 255.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `amount` - From -1 to 1. Positive values brighten the image and negative values darken it;
+/// 1 turns every pixel white and -1 turns every pixel black.
+/// # Example
+///
+/// ```
+/// // For example, to darken an image by 20%:
+/// use photon::effects::brightness;
+/// brightness(&mut img, -0.2);
+/// ```
+#[wasm_bindgen]
+pub fn brightness(photon_image: &mut PhotonImage, amount: f32) {
+    let offset = (num::clamp(amount, -1.0, 1.0) * 255.0).round() as i16;
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        for value in px.iter_mut().take(3) {
+            *value = num::clamp(*value as i16 + offset, 0, 255) as u8;
         }
     }
-    photon_image.raw_pixels = img.raw_pixels();
 }
 
 /// Adjust the contrast of an image by a factor.
@@ -541,39 +698,30 @@ pub fn adjust_contrast(mut photon_image: &mut PhotonImage, contrast: f32) {
     photon_image.raw_pixels = img.raw_pixels();
 }
 
-/// Tint an image by adding an offset to averaged RGB channel values.
-/// 
+/// Tint an image, shifting every pixel towards a colour.
+///
 /// # Arguments
-/// * `img` - A PhotonImage that contains a view into the image.
-/// * `r_offset` - The amount the  R channel should be incremented by.
-/// * `g_offset` - The amount the G channel should be incremented by.
-/// * `b_offset` - The amount the B channel should be incremented by.
+/// * `photon_image` - A PhotonImage.
+/// * `colour` - The colour to tint the image with.
+/// * `strength` - From 0 to 1. 0 leaves the image unchanged and 1 fills it with the colour.
 /// # Example
 ///
 /// ```
-/// // For example, to tint an image of type `PhotonImage`:
-/// photon::tint(img, 10, 20, 15);
+/// // For example, to give an image a warm orange tint:
+/// use photon::effects::tint;
+/// use photon::Rgb;
+/// tint(&mut img, Rgb::new(255, 140, 0), 0.2);
 /// ```
-/// 
 #[wasm_bindgen]
-pub fn tint(mut photon_image: &mut PhotonImage, r_offset: u32, g_offset: u32, b_offset: u32) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
-    let (width, height) = img.dimensions();
-
-    for x in 0..width {
-        for y in 0..height {
-            let mut px = img.get_pixel(x, y);
-            let (r_val, g_val, b_val) = (px.data[0] as u32, px.data[1] as u32, px.data[2] as u32);
-            
-            px.data[0] = if r_val as u32 + r_offset < 255 { r_val as u8 + r_offset as u8} else { 255 };
-            px.data[1] = if g_val as u32 + g_offset < 255 { g_val as u8 + g_offset as u8} else { 255 };
-            px.data[2] = if b_val as u32 + b_offset < 255 { b_val as u8 + b_offset as u8} else { 255 };
-
-            img.put_pixel(x, y, px);
+pub fn tint(photon_image: &mut PhotonImage, colour: Rgb, strength: f32) {
+    let strength = num::clamp(strength, 0.0, 1.0);
+    let colour = [colour.r as f32, colour.g as f32, colour.b as f32];
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        for (channel, value) in px.iter_mut().take(3).enumerate() {
+            let tinted = *value as f32 + (colour[channel] - *value as f32) * strength;
+            *value = num::clamp(tinted.round(), 0.0, 255.0) as u8;
         }
     }
-    let raw_pixels = img.raw_pixels();
-    photon_image.raw_pixels = raw_pixels;
 }
 
 
@@ -622,4 +770,84 @@ pub fn vertical_strips(mut photon_image: &mut PhotonImage, num_strips: u8) {
 
     let raw_pixels = img.raw_pixels();
     photon_image.raw_pixels = raw_pixels;
-}
\ No newline at end of file
+}
+
+/// Add a vignette, darkening or lightening the image towards its edges.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// * `strength` - The strength of the effect, from -1 to 1. Positive values darken the edges by
+/// multiplying them with `colour`, and negative values lighten them by screening them with `colour`.
+/// * `radius` - The distance from the centre at which the vignette starts, where 1 reaches the middle of the image's edges.
+/// * `feather` - The width of the transition from untouched to fully vignetted, on the same scale as `radius`.
+/// * `roundness` - The shape, from 0 to 1. 0 gives an ellipse that follows the image's proportions, 1 gives a circle.
+/// * `colour` - The colour of the vignette. Use black for a classic darkening vignette, white for a lightening one.
+/// # Example
+///
+/// ```
+/// // For example, to add a soft, dark, circular vignette:
+/// use photon::effects::vignette;
+/// use photon::Rgb;
+/// vignette(&mut img, 0.6, 0.5, 0.6, 1.0, Rgb::new(0, 0, 0));
+/// ```
+#[wasm_bindgen]
+pub fn vignette(photon_image: &mut PhotonImage, strength: f32, radius: f32, feather: f32, roundness: f32, colour: Rgb) {
+    vignette_with_centre(photon_image, strength, radius, feather, roundness, colour, 0.5, 0.5);
+}
+
+/// Add a vignette around an off-centre point, for example to draw the eye towards the subject of a photo.
+///
+/// # Arguments
+/// * `img` - A PhotonImage that contains a view into the image.
+/// * `strength` - The strength of the effect, from -1 to 1. Positive values darken, negative values lighten.
+/// * `radius` - The distance from the centre at which the vignette starts, where 1 reaches the middle of the image's edges.
+/// * `feather` - The width of the transition from untouched to fully vignetted, on the same scale as `radius`.
+/// * `roundness` - The shape, from 0 to 1. 0 gives an ellipse that follows the image's proportions, 1 gives a circle.
+/// * `colour` - The colour of the vignette.
+/// * `centre_x` - The horizontal position of the centre, from 0 (left edge) to 1 (right edge).
+/// * `centre_y` - The vertical position of the centre, from 0 (top edge) to 1 (bottom edge).
+/// # Example
+///
+/// ```
+/// // For example, to centre a vignette on a subject in the upper third of the image:
+/// use photon::effects::vignette_with_centre;
+/// use photon::Rgb;
+/// vignette_with_centre(&mut img, 0.5, 0.4, 0.7, 0.5, Rgb::new(0, 0, 0), 0.5, 0.33);
+/// ```
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn vignette_with_centre(photon_image: &mut PhotonImage, strength: f32, radius: f32, feather: f32, roundness: f32,
+                            colour: Rgb, centre_x: f32, centre_y: f32) {
+    let strength = num::clamp(strength, -1.0, 1.0);
+    let roundness = num::clamp(roundness, 0.0, 1.0);
+    // A feather of 0 would divide by zero, so keep a hard edge at least a tiny bit soft
+    let feather = feather.max(1e-3);
+    let width = photon_image.width as f32;
+    let height = photon_image.height as f32;
+
+    // Scale factors that turn a circle into an ellipse filling the image, blended by roundness
+    let longest = width.max(height);
+    let scale_x = 1.0 + roundness * (width / longest - 1.0);
+    let scale_y = 1.0 + roundness * (height / longest - 1.0);
+    let tint = [colour.r as f32 / 255.0, colour.g as f32 / 255.0, colour.b as f32 / 255.0];
+
+    for (i, px) in photon_image.raw_pixels.chunks_mut(4).enumerate() {
+        let x = (i as u32 % photon_image.width) as f32;
+        let y = (i as u32 / photon_image.width) as f32;
+        let dx = ((x + 0.5) / width - centre_x) * 2.0 * scale_x;
+        let dy = ((y + 0.5) / height - centre_y) * 2.0 * scale_y;
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        let t = num::clamp((distance - radius) / feather, 0.0, 1.0);
+        let weight = t * t * (3.0 - 2.0 * t) * strength.abs();
+        if weight == 0.0 {
+            continue;
+        }
+
+        for (channel, c) in px.iter_mut().take(3).zip(tint.iter()) {
+            let v = *channel as f32 / 255.0;
+            let vignetted = if strength > 0.0 { v * c } else { 1.0 - (1.0 - v) * (1.0 - c) };
+            *channel = ((v + (vignetted - v) * weight) * 255.0).round() as u8;
+        }
+    }
+}
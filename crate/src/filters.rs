@@ -1,13 +1,17 @@
 //! Preset color filters.
 
 extern crate image;
-use image::{GenericImage, GenericImageView};
 use wasm_bindgen::prelude::*;
-use crate::{PhotonImage};
-use crate::{helpers, monochrome, effects};
-use crate::channels::{alter_two_channels, alter_blue_channel};
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::{PhotonImage, Rgb};
+use crate::{monochrome, effects};
+use crate::channels::{alter_channels, mix_pixels, ChannelMask};
+use crate::mask::apply_with_mask;
+use crate::lut::{apply_lut, apply_lut_1d, Lut1D, Lut3D};
+use crate::colour_spaces::{adjust, ColourAdjustment, ColourSpace};
 
-/// Solarization on the Blue channel.
+/// Invert the Blue channel.
 /// 
 /// # Arguments
 /// * `img` - A PhotonImage.
@@ -17,23 +21,11 @@ use crate::channels::{alter_two_channels, alter_blue_channel};
 /// photon::filters::neue(&mut img);
 /// ```
 #[wasm_bindgen]
-pub fn neue(mut photon_image: &mut PhotonImage) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
-    let (width, height) = img.dimensions();
-
-    for x in 0..width {
-        for y in 0..height {
-            let mut px = img.get_pixel(x, y);
-            if 255 as i32 - px.data[2] as i32 > 0 {
-                px.data[2] = 255 - px.data[2];
-            }
-            img.put_pixel(x, y, px);
-        }
-    }
-    photon_image.raw_pixels = img.raw_pixels();
+pub fn neue(photon_image: &mut PhotonImage) {
+    effects::invert_channels(photon_image, ChannelMask::new(false, false, true));
 }
 
-/// Solarization on the Red and Green channels.
+/// Invert the Red and Green channels.
 /// 
 /// # Arguments
 /// * `img` - A PhotonImage.
@@ -43,25 +35,11 @@ pub fn neue(mut photon_image: &mut PhotonImage) {
 /// photon::filters::lix(&mut img);
 /// ```
 #[wasm_bindgen]
-pub fn lix(mut photon_image: &mut PhotonImage) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
-
-    let (width, height) = img.dimensions();
-
-    for x in 0..width {
-        for y in 0..height {
-            let mut px = img.get_pixel(x, y);
-            
-            px.data[0] = 255 - px.data[0];
-            px.data[1] = 255 - px.data[1];
-            
-            img.put_pixel(x, y, px);
-        }
-    }
-    photon_image.raw_pixels = img.raw_pixels();
+pub fn lix(photon_image: &mut PhotonImage) {
+    effects::invert_channels(photon_image, ChannelMask::new(true, true, false));
 }
 
-/// Solarization on the Red and Blue channels.
+/// Invert the Red and Blue channels.
 /// 
 /// # Arguments
 /// * `img` - A PhotonImage.
@@ -71,72 +49,587 @@ pub fn lix(mut photon_image: &mut PhotonImage) {
 /// photon::filters::ryo(&mut img);
 /// ```
 #[wasm_bindgen]
-pub fn ryo(mut photon_image: &mut PhotonImage) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
+pub fn ryo(photon_image: &mut PhotonImage) {
+    effects::invert_channels(photon_image, ChannelMask::new(true, false, true));
+}
+
+
+/// A vignette with a soft edge, used by several presets.
+fn soft_vignette(strength: f32) -> FilterStep {
+    FilterStep::Vignette(strength, 0.55, 0.8, 0.5, Rgb::new(0, 0, 0))
+}
+
+/// Tone curves for a preset. The presets' control points are constant, so they're always valid.
+fn preset_curves(red: Vec<f32>, green: Vec<f32>, blue: Vec<f32>) -> FilterStep {
+    FilterStep::Curves(Lut1D::from_control_points(red, green, blue).expect("Preset curves must be valid"))
+}
+
+fn clarendon_pipeline() -> FilterPipeline {
+    FilterPipeline::from_steps(vec![
+        preset_curves(
+            vec![0.0, 0.0, 0.25, 0.2, 0.75, 0.82, 1.0, 1.0],
+            vec![0.0, 0.0, 0.25, 0.21, 0.75, 0.8, 1.0, 1.0],
+            vec![0.0, 0.06, 0.25, 0.26, 0.75, 0.78, 1.0, 0.98],
+        ),
+        FilterStep::Adjust(ColourSpace::Lch, ColourAdjustment::Saturate, 0.35),
+        FilterStep::Tint(Rgb::new(127, 187, 227), 0.08),
+    ])
+}
+
+fn gingham_pipeline() -> FilterPipeline {
+    let fade = vec![0.0, 0.1, 0.5, 0.52, 1.0, 0.95];
+    FilterPipeline::from_steps(vec![
+        preset_curves(fade.clone(), fade.clone(), fade),
+        FilterStep::Adjust(ColourSpace::Lch, ColourAdjustment::ShiftHue, -10.0),
+        FilterStep::Adjust(ColourSpace::Lch, ColourAdjustment::Desaturate, 0.2),
+        FilterStep::Tint(Rgb::new(230, 230, 250), 0.15),
+    ])
+}
+
+fn juno_pipeline() -> FilterPipeline {
+    FilterPipeline::from_steps(vec![
+        preset_curves(
+            vec![0.0, 0.0, 0.3, 0.3, 0.7, 0.78, 1.0, 1.0],
+            vec![0.0, 0.0, 0.3, 0.28, 0.7, 0.75, 1.0, 1.0],
+            vec![0.0, 0.0, 0.3, 0.26, 0.7, 0.68, 1.0, 0.94],
+        ),
+        FilterStep::Adjust(ColourSpace::Lch, ColourAdjustment::Saturate, 0.4),
+        FilterStep::Tint(Rgb::new(255, 214, 150), 0.06),
+        soft_vignette(0.2),
+    ])
+}
+
+fn lark_pipeline() -> FilterPipeline {
+    FilterPipeline::from_steps(vec![
+        preset_curves(
+            vec![0.0, 0.08, 0.5, 0.56, 1.0, 1.0],
+            vec![0.0, 0.08, 0.5, 0.58, 1.0, 1.0],
+            vec![0.0, 0.1, 0.5, 0.6, 1.0, 1.0],
+        ),
+        FilterStep::Adjust(ColourSpace::Lch, ColourAdjustment::Desaturate, 0.15),
+        FilterStep::Tint(Rgb::new(217, 217, 217), 0.08),
+    ])
+}
+
+fn valencia_pipeline() -> FilterPipeline {
+    FilterPipeline::from_steps(vec![
+        preset_curves(
+            vec![0.0, 0.08, 0.5, 0.55, 1.0, 1.0],
+            vec![0.0, 0.05, 0.5, 0.51, 1.0, 0.97],
+            vec![0.0, 0.03, 0.5, 0.45, 1.0, 0.88],
+        ),
+        FilterStep::Adjust(ColourSpace::Lch, ColourAdjustment::Desaturate, 0.08),
+        FilterStep::Tint(Rgb::new(255, 225, 180), 0.12),
+        soft_vignette(0.25),
+    ])
+}
+
+fn nashville_pipeline() -> FilterPipeline {
+    FilterPipeline::from_steps(vec![
+        preset_curves(
+            vec![0.0, 0.1, 0.5, 0.56, 1.0, 1.0],
+            vec![0.0, 0.05, 0.5, 0.5, 1.0, 0.94],
+            vec![0.0, 0.22, 0.5, 0.46, 1.0, 0.8],
+        ),
+        FilterStep::Adjust(ColourSpace::Lch, ColourAdjustment::Saturate, 0.2),
+        FilterStep::Tint(Rgb::new(247, 176, 153), 0.15),
+        soft_vignette(0.3),
+    ])
+}
+
+/// The preset filters built into every `FilterRegistry`, with their descriptions and pipelines.
+fn builtin_filters() -> Vec<(&'static str, &'static str, FilterPipeline)> {
+    let alter = |r, g, b| FilterPipeline { steps: vec![FilterStep::AlterChannels(r, g, b)] };
+    let mono = |r, g, b| FilterPipeline { steps: vec![FilterStep::Monochrome(r, g, b)] };
+    let invert = |r, g, b| FilterPipeline { steps: vec![FilterStep::InvertChannels(ChannelMask::new(r, g, b))] };
+    vec![
+        ("oceanic", "Add an aquamarine-tinted hue to an image.", alter(0, 9, 173)),
+        ("islands", "Aquamarine tint.", alter(0, 24, 95)),
+        ("marine", "Add a green/blue mixed hue to an image.", alter(0, 14, 119)),
+        ("seagreen", "Dark green hue, with tones of blue.", alter(0, 68, 62)),
+        ("flagblue", "Royal blue tint.", alter(0, 0, 131)),
+        ("liquid", "Blue-inspired tint.", alter(0, 10, 75)),
+        ("diamante", "Custom filter with a blue/turquoise tint.", alter(0, 82, 87)),
+        ("radio", "Fallout-style radio effect.", mono(5, 40, 20)),
+        ("twenties", "Slight-blue tinted historical effect.", mono(18, 12, 20)),
+        ("rosetint", "Rose-tinted filter.", mono(80, 20, 31)),
+        ("mauve", "Purple-infused filter.", mono(90, 40, 80)),
+        ("bluechrome", "Blue monochrome effect.", mono(20, 30, 60)),
+        ("vintage", "Vintage filter with a red tint.", alter(120, 70, 13)),
+        ("perfume", "Increase the Blue channel, with moderate increases in the Red and Green channels.", alter(80, 40, 120)),
+        ("serenity", "Custom filter with an increase in the Blue channel's values.", alter(10, 40, 90)),
+        ("neue", "Invert the Blue channel.", invert(false, false, true)),
+        ("lix", "Invert the Red and Green channels.", invert(true, true, false)),
+        ("ryo", "Invert the Red and Blue channels.", invert(true, false, true)),
+        ("clarendon", "Punchy contrast and saturation, with cool shadows and warm highlights.", clarendon_pipeline()),
+        ("gingham", "Faded, low-contrast vintage look with a soft lavender cast.", gingham_pipeline()),
+        ("juno", "Vivid, warm colours with bright highlights.", juno_pipeline()),
+        ("lark", "Bright and airy, with lifted shadows and muted colours.", lark_pipeline()),
+        ("valencia", "Warm, faded tones with a gentle vignette.", valencia_pipeline()),
+        ("nashville", "Pink highlights, blue shadows and a warm vintage fade.", nashville_pipeline()),
+    ]
+}
+
+/// Errors from looking up or registering filters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterError {
+    /// No filter with this name is registered.
+    UnknownFilter(String),
+    /// A built-in filter with this name exists, and can't be replaced.
+    BuiltinFilter(String),
+    /// Filter names can't be empty.
+    EmptyName,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterError::UnknownFilter(name) => write!(f, "Unknown filter '{}'", name),
+            FilterError::BuiltinFilter(name) => write!(f, "'{}' is a built-in filter and can't be replaced", name),
+            FilterError::EmptyName => write!(f, "Filter names can't be empty"),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl From<FilterError> for JsValue {
+    fn from(error: FilterError) -> JsValue {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+/// A single operation in a `FilterPipeline`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum FilterStep {
+    /// Add an amount to each of the Red, Green and Blue channels. See `channels::alter_channels`.
+    AlterChannels(i16, i16, i16),
+    /// Greyscale with a colour offset per channel. See `monochrome::monochrome`.
+    Monochrome(u32, u32, u32),
+    /// See `effects::invert_channels`.
+    InvertChannels(ChannelMask),
+    /// See `effects::brightness`.
+    Brightness(f32),
+    /// See `effects::adjust_contrast`.
+    Contrast(f32),
+    /// See `effects::tint`.
+    Tint(Rgb, f32),
+    /// See `effects::solarize`.
+    Solarize(u8, ChannelMask),
+    /// See `effects::posterize`.
+    Posterize(u8),
+    /// See `lut::apply_lut`.
+    Lut(Lut3D),
+    /// Tone curves. See `lut::apply_lut_1d`.
+    Curves(Lut1D),
+    /// See `colour_spaces::adjust`.
+    Adjust(ColourSpace, ColourAdjustment, f32),
+    /// Strength, radius, feather, roundness and colour. See `effects::vignette`.
+    Vignette(f32, f32, f32, f32, Rgb),
+}
+
+impl FilterStep {
+    fn apply(&self, photon_image: &mut PhotonImage) {
+        match self {
+            FilterStep::AlterChannels(r, g, b) => alter_channels(photon_image, *r, *g, *b),
+            FilterStep::Monochrome(r, g, b) => monochrome::monochrome(photon_image, *r, *g, *b),
+            FilterStep::InvertChannels(channels) => effects::invert_channels(photon_image, *channels),
+            FilterStep::Brightness(amount) => effects::brightness(photon_image, *amount),
+            FilterStep::Contrast(contrast) => effects::adjust_contrast(photon_image, *contrast),
+            FilterStep::Tint(colour, strength) => effects::tint(photon_image, *colour, *strength),
+            FilterStep::Solarize(threshold, channels) => effects::solarize(photon_image, *threshold, *channels),
+            FilterStep::Posterize(levels) => effects::posterize(photon_image, *levels),
+            FilterStep::Lut(lut) => apply_lut(photon_image, lut),
+            FilterStep::Curves(lut) => apply_lut_1d(photon_image, lut),
+            FilterStep::Adjust(space, adjustment, amount) => adjust(photon_image, *space, *adjustment, *amount),
+            FilterStep::Vignette(strength, radius, feather, roundness, colour) => {
+                effects::vignette(photon_image, *strength, *radius, *feather, *roundness, *colour)
+            }
+        }
+    }
+}
+
+/// A custom filter, built as a sequence of existing operations that are applied in order.
+///
+/// # Example
+///
+/// ```
+/// // For example, to build a faded, warm filter:
+/// use photon::filters::FilterPipeline;
+/// use photon::Rgb;
+/// let mut pipeline = FilterPipeline::new();
+/// pipeline.contrast(-30.0);
+/// pipeline.tint(Rgb::new(255, 160, 60), 0.15);
+/// pipeline.apply(&mut img);
+/// ```
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FilterPipeline {
+    steps: Vec<FilterStep>,
+}
+
+#[wasm_bindgen]
+impl FilterPipeline {
+    #[wasm_bindgen(constructor)]
+    /// Create an empty pipeline.
+    pub fn new() -> FilterPipeline {
+        FilterPipeline::default()
+    }
+
+    /// Add an amount, from -255 to 255, to each of the Red, Green and Blue channels.
+    pub fn alter_channels(&mut self, r_amt: i16, g_amt: i16, b_amt: i16) {
+        self.steps.push(FilterStep::AlterChannels(r_amt, g_amt, b_amt));
+    }
+
+    /// Greyscale the image, then add an offset to each channel.
+    pub fn monochrome(&mut self, r_offset: u32, g_offset: u32, b_offset: u32) {
+        self.steps.push(FilterStep::Monochrome(r_offset, g_offset, b_offset));
+    }
+
+    /// Invert a selection of channels.
+    pub fn invert_channels(&mut self, channels: ChannelMask) {
+        self.steps.push(FilterStep::InvertChannels(channels));
+    }
+
+    /// Brighten or darken the image, by an amount from -1 to 1.
+    pub fn brightness(&mut self, amount: f32) {
+        self.steps.push(FilterStep::Brightness(amount));
+    }
+
+    /// Adjust the contrast, by a factor from -255 to 255.
+    pub fn contrast(&mut self, contrast: f32) {
+        self.steps.push(FilterStep::Contrast(contrast));
+    }
+
+    /// Shift every pixel towards a colour, with a strength from 0 to 1.
+    pub fn tint(&mut self, colour: Rgb, strength: f32) {
+        self.steps.push(FilterStep::Tint(colour, strength));
+    }
+
+    /// Invert channel values above a threshold.
+    pub fn solarize(&mut self, threshold: u8, channels: ChannelMask) {
+        self.steps.push(FilterStep::Solarize(threshold, channels));
+    }
+
+    /// Reduce each channel to a number of levels.
+    pub fn posterize(&mut self, levels_per_channel: u8) {
+        self.steps.push(FilterStep::Posterize(levels_per_channel));
+    }
+
+    /// Apply a 3D LUT.
+    pub fn lut(&mut self, lut: &Lut3D) {
+        self.steps.push(FilterStep::Lut(lut.clone()));
+    }
+
+    /// Apply tone curves, such as those made with `Lut1D::from_control_points`.
+    pub fn curves(&mut self, curves: &Lut1D) {
+        self.steps.push(FilterStep::Curves(curves.clone()));
+    }
+
+    /// Adjust colours in a colour space, as with `colour_spaces::adjust`.
+    pub fn adjust(&mut self, space: ColourSpace, adjustment: ColourAdjustment, amount: f32) {
+        self.steps.push(FilterStep::Adjust(space, adjustment, amount));
+    }
+
+    /// Add a vignette, as with `effects::vignette`.
+    pub fn vignette(&mut self, strength: f32, radius: f32, feather: f32, roundness: f32, colour: Rgb) {
+        self.steps.push(FilterStep::Vignette(strength, radius, feather, roundness, colour));
+    }
+
+    /// Get the number of steps in the pipeline.
+    pub fn get_length(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Apply every step of the pipeline to an image, in order.
+    pub fn apply(&self, photon_image: &mut PhotonImage) {
+        for step in self.steps.iter() {
+            step.apply(photon_image);
+        }
+    }
+
+    /// Apply the pipeline only where a greyscale mask is white, blending with the original image
+    /// where it's grey. See `mask::blend_with_mask`.
+    pub fn apply_with_mask(&self, photon_image: &mut PhotonImage, mask: &PhotonImage) {
+        apply_with_mask(photon_image, mask, |img| self.apply(img));
+    }
+
+    /// Apply the pipeline, then blend the result with the original image.
+    /// An `intensity` of 0 leaves the image unchanged, and 1 gives the full effect.
+    pub fn apply_with_intensity(&self, photon_image: &mut PhotonImage, intensity: f32) {
+        // NaN would pass through the clamp and mix every channel to 0, so it leaves the image unchanged like 0
+        if intensity <= 0.0 || intensity.is_nan() {
+            return;
+        }
+        let intensity = num::clamp(intensity, 0.0, 1.0);
+        let original = photon_image.raw_pixels.clone();
+        self.apply(photon_image);
+        if intensity >= 1.0 {
+            return;
+        }
+        for (px, original) in photon_image.raw_pixels.chunks_mut(4).zip(original.chunks(4)) {
+            let mixed = mix_pixels([original[0], original[1], original[2], px[3]], [px[0], px[1], px[2], px[3]], intensity);
+            px.copy_from_slice(&mixed);
+        }
+    }
+}
+
+impl FilterPipeline {
+    /// Create a pipeline from a list of steps.
+    pub fn from_steps(steps: Vec<FilterStep>) -> FilterPipeline {
+        FilterPipeline { steps }
+    }
+
+    /// Get the steps of the pipeline.
+    pub fn steps(&self) -> &[FilterStep] {
+        &self.steps
+    }
+}
+
+/// The name and description of a registered filter.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FilterInfo {
+    name: String,
+    description: String,
+    builtin: bool,
+}
+
+#[wasm_bindgen]
+impl FilterInfo {
+    /// Get the name of the filter.
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Get the description of the filter.
+    pub fn get_description(&self) -> String {
+        self.description.clone()
+    }
+
+    /// Get whether the filter is one of photon's preset filters, rather than a custom filter.
+    pub fn get_builtin(&self) -> bool {
+        self.builtin
+    }
+}
+
+/// A collection of named filters: the preset filters, along with any custom filters registered by the user.
+///
+/// # Example
+///
+/// ```
+/// // For example, to register a custom filter and apply it by name:
+/// use photon::filters::{FilterPipeline, FilterRegistry};
+/// let mut registry = FilterRegistry::new();
+/// let mut pipeline = FilterPipeline::new();
+/// pipeline.monochrome(20, 10, 0);
+/// pipeline.contrast(40.0);
+/// registry.register_filter("sepia_punch", "Punchy warm monochrome.", &pipeline).unwrap();
+/// registry.apply_filter(&mut img, "sepia_punch").unwrap();
+/// ```
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct FilterRegistry {
+    filters: Vec<(FilterInfo, FilterPipeline)>,
+}
 
-    let (width, height) = img.dimensions();
+impl Default for FilterRegistry {
+    fn default() -> FilterRegistry {
+        let filters = builtin_filters()
+            .into_iter()
+            .map(|(name, description, pipeline)| {
+                (FilterInfo { name: name.to_string(), description: description.to_string(), builtin: true }, pipeline)
+            })
+            .collect();
+        FilterRegistry { filters }
+    }
+}
 
-    for x in 0..width {
-        for y in 0..height {
-            let mut px = img.get_pixel(x, y);
-            if 255 as i32 - px.data[2] as i32 > 0 {
-                px.data[0] = 255 - px.data[0];
-                px.data[2] = 255 - px.data[2];
+#[wasm_bindgen]
+impl FilterRegistry {
+    #[wasm_bindgen(constructor)]
+    /// Create a registry containing the preset filters.
+    pub fn new() -> FilterRegistry {
+        FilterRegistry::default()
+    }
+
+    /// List the names and descriptions of every registered filter, presets first, in the order they were registered.
+    pub fn list_filters(&self) -> Vec<FilterInfo> {
+        self.filters.iter().map(|(info, _)| info.clone()).collect()
+    }
+
+    /// Get whether a filter with this name is registered.
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.iter().any(|(info, _)| info.name == name)
+    }
+
+    /// Register a custom filter. A custom filter with the same name is replaced, but preset filters can't be.
+    pub fn register_filter(&mut self, name: &str, description: &str, pipeline: &FilterPipeline) -> Result<(), FilterError> {
+        if name.is_empty() {
+            return Err(FilterError::EmptyName);
+        }
+        let info = FilterInfo { name: name.to_string(), description: description.to_string(), builtin: false };
+        match self.filters.iter_mut().find(|(existing, _)| existing.name == name) {
+            Some((existing, _)) if existing.builtin => Err(FilterError::BuiltinFilter(name.to_string())),
+            Some(entry) => {
+                *entry = (info, pipeline.clone());
+                Ok(())
+            }
+            None => {
+                self.filters.push((info, pipeline.clone()));
+                Ok(())
             }
-            img.put_pixel(x, y, px);
         }
     }
-    photon_image.raw_pixels = img.raw_pixels();
-}
-
-
-/// Apply a filter to an image. Over 20 filters are available.
-/// The filters are as follows:
-/// * **oceanic**: Add an aquamarine-tinted hue to an image.
-/// * **islands**: Aquamarine tint.
-/// * **marine**: Add a green/blue mixed hue to an image.
-/// * **seagreen**: Dark green hue, with tones of blue.
-/// * **flagblue**: Royal blue tint
-/// * **liquid**: Blue-inspired tint.
-/// * **diamante**: Custom filter with a blue/turquoise tint.
-/// * **radio**: Fallout-style radio effect.
-/// * **twenties**: Slight-blue tinted historical effect.
-/// * **rosetint**: Rose-tinted filter.
-/// * **mauve**: Purple-infused filter.
-/// * **bluechrome**: Blue monochrome effect.
-/// * **vintage**: Vintage filter with a red tint.
-/// * **perfume**: Increase the blue channel, with moderate increases in the Red and Green channels.
-/// * **serenity**: Custom filter with an increase in the Blue channel's values.
+
+    /// Remove a custom filter.
+    pub fn unregister_filter(&mut self, name: &str) -> Result<(), FilterError> {
+        match self.filters.iter().position(|(info, _)| info.name == name) {
+            Some(i) if self.filters[i].0.builtin => Err(FilterError::BuiltinFilter(name.to_string())),
+            Some(i) => {
+                self.filters.remove(i);
+                Ok(())
+            }
+            None => Err(FilterError::UnknownFilter(name.to_string())),
+        }
+    }
+
+    /// Apply a registered filter to an image.
+    pub fn apply_filter(&self, photon_image: &mut PhotonImage, name: &str) -> Result<(), FilterError> {
+        self.apply_filter_with_intensity(photon_image, name, 1.0)
+    }
+
+    /// Apply a registered filter to an image, blended with the original image.
+    /// An `intensity` of 0 leaves the image unchanged, and 1 gives the full effect.
+    pub fn apply_filter_with_intensity(&self, photon_image: &mut PhotonImage, name: &str, intensity: f32) -> Result<(), FilterError> {
+        match self.get_pipeline(name) {
+            Some(pipeline) => {
+                pipeline.apply_with_intensity(photon_image, intensity);
+                Ok(())
+            }
+            None => Err(FilterError::UnknownFilter(name.to_string())),
+        }
+    }
+}
+
+impl FilterRegistry {
+    /// Get the pipeline of a registered filter.
+    pub fn get_pipeline(&self, name: &str) -> Option<&FilterPipeline> {
+        self.filters.iter().find(|(info, _)| info.name == name).map(|(_, pipeline)| pipeline)
+    }
+}
+
+/// Apply a preset filter to an image. Use `FilterRegistry::list_filters` to get the names and descriptions of every preset.
+///
 /// # Arguments
 /// * `img` - A PhotonImage.
-/// * `blend_mode` - The blending mode to use. See above for complete list of blend modes available.
+/// * `filter_name` - The name of the filter, such as "oceanic", "vintage" or "bluechrome".
+///
+/// Returns an error if there is no preset filter with this name.
 /// # Example
 ///
 /// ```
 /// // For example, to add a filter called "vintage" to an image:
 /// use photon::filters;
-/// photon::filters::filter(&mut img, "vintage");
-/// ```
-#[wasm_bindgen]
-pub fn filter(img: &mut PhotonImage, filter_name: &str) {
-
-    match filter_name {
-        // Match filter name to its corresponding function.
-        "oceanic" => alter_two_channels(img, 1, 9, 2, 173),
-        "islands" => alter_two_channels(img, 1, 24, 2, 95),
-        "marine" => alter_two_channels(img, 1, 14, 2, 119),
-        "seagreen" => alter_two_channels(img, 1, 68, 2, 62),
-        "flagblue" => alter_blue_channel(img, 131),
-        "diamante" => alter_two_channels(img, 1, 82, 2, 87),
-        "liquid" => alter_two_channels(img, 1, 10, 2, 75),
-        "radio" => monochrome::monochrome(img, 5, 40, 20),
-        "twenties" => monochrome::monochrome(img, 18, 12, 20),
-        "rosetint" =>  monochrome::monochrome(img, 80, 20, 31),
-        "mauve" => monochrome::monochrome(img, 90, 40, 80),
-        "bluechrome" => monochrome::monochrome(img, 20, 30, 60),
-        "vintage" => effects::tint(img, 120, 70, 13),
-        "perfume" => effects::tint(img, 80, 40, 120),
-        "serenity" => effects::tint(img, 10, 40, 90),
-        _ => monochrome::monochrome(img, 90, 40, 80),
-    };
-}
\ No newline at end of file
+/// photon::filters::filter(&mut img, "vintage").unwrap();
+/// ```
+#[wasm_bindgen]
+pub fn filter(img: &mut PhotonImage, filter_name: &str) -> Result<(), FilterError> {
+    FilterRegistry::new().apply_filter(img, filter_name)
+}
+
+/// Apply the clarendon preset: punchy contrast and saturation, with cool shadows and warm highlights.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `intensity` - From 0 to 1. 0 leaves the image unchanged, and 1 gives the full effect.
+/// # Example
+///
+/// ```
+/// use photon::filters::clarendon;
+/// clarendon(&mut img, 0.8);
+/// ```
+#[wasm_bindgen]
+pub fn clarendon(photon_image: &mut PhotonImage, intensity: f32) {
+    clarendon_pipeline().apply_with_intensity(photon_image, intensity);
+}
+
+/// Apply the gingham preset: faded, low-contrast vintage look with a soft lavender cast.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `intensity` - From 0 to 1. 0 leaves the image unchanged, and 1 gives the full effect.
+/// # Example
+///
+/// ```
+/// use photon::filters::gingham;
+/// gingham(&mut img, 1.0);
+/// ```
+#[wasm_bindgen]
+pub fn gingham(photon_image: &mut PhotonImage, intensity: f32) {
+    gingham_pipeline().apply_with_intensity(photon_image, intensity);
+}
+
+/// Apply the juno preset: vivid, warm colours with bright highlights.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `intensity` - From 0 to 1. 0 leaves the image unchanged, and 1 gives the full effect.
+/// # Example
+///
+/// ```
+/// use photon::filters::juno;
+/// juno(&mut img, 0.7);
+/// ```
+#[wasm_bindgen]
+pub fn juno(photon_image: &mut PhotonImage, intensity: f32) {
+    juno_pipeline().apply_with_intensity(photon_image, intensity);
+}
+
+/// Apply the lark preset: bright and airy, with lifted shadows and muted colours.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `intensity` - From 0 to 1. 0 leaves the image unchanged, and 1 gives the full effect.
+/// # Example
+///
+/// ```
+/// use photon::filters::lark;
+/// lark(&mut img, 1.0);
+/// ```
+#[wasm_bindgen]
+pub fn lark(photon_image: &mut PhotonImage, intensity: f32) {
+    lark_pipeline().apply_with_intensity(photon_image, intensity);
+}
+
+/// Apply the valencia preset: warm, faded tones with a gentle vignette.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `intensity` - From 0 to 1. 0 leaves the image unchanged, and 1 gives the full effect.
+/// # Example
+///
+/// ```
+/// use photon::filters::valencia;
+/// valencia(&mut img, 0.9);
+/// ```
+#[wasm_bindgen]
+pub fn valencia(photon_image: &mut PhotonImage, intensity: f32) {
+    valencia_pipeline().apply_with_intensity(photon_image, intensity);
+}
+
+/// Apply the nashville preset: pink highlights, blue shadows and a warm vintage fade.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `intensity` - From 0 to 1. 0 leaves the image unchanged, and 1 gives the full effect.
+/// # Example
+///
+/// ```
+/// use photon::filters::nashville;
+/// nashville(&mut img, 0.6);
+/// ```
+#[wasm_bindgen]
+pub fn nashville(photon_image: &mut PhotonImage, intensity: f32) {
+    nashville_pipeline().apply_with_intensity(photon_image, intensity);
+}
@@ -39,8 +39,10 @@
 //! View the [official demo of WASM in action](https://silvia-odwyer.github.io/photon).
 
 use wasm_bindgen::prelude::*;
-use web_sys::{CanvasRenderingContext2d, ImageData, HtmlCanvasElement};
+use web_sys::{CanvasRenderingContext2d, ImageData, HtmlCanvasElement, ImageBitmap, OffscreenCanvas, OffscreenCanvasRenderingContext2d};
 use wasm_bindgen::Clamped;
+use wasm_bindgen::JsCast;
+use js_sys::{Reflect, Uint8ClampedArray};
 use image::{GenericImage, GenericImageView};
 use base64::decode;
 use serde::{Serialize, Deserialize};
@@ -54,7 +56,7 @@ use serde::{Serialize, Deserialize};
 /// Provides the image's height, width, and contains the image's raw pixels.
 /// For use when communicating between JS and WASM, and also natively. 
 #[wasm_bindgen]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PhotonImage {
     raw_pixels: Vec<u8>,
     width: u32, 
@@ -108,6 +110,71 @@ impl PhotonImage {
         new_img_data
     }
 
+    /// Get a `Uint8ClampedArray` view straight over the PhotonImage's pixels in wasm memory, without copying them.
+    ///
+    /// Writing to the view changes the PhotonImage's pixels in place. The view is only valid until the next call
+    /// into photon: any call may grow wasm memory, which detaches the view, leaving it empty, and functions that
+    /// change the image's size, such as `resize` or `crop`, move the pixels elsewhere. Free the PhotonImage only
+    /// once you're done with the view. Get a fresh view after every call, rather than keeping one around.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // For example, in JavaScript, to halve the alpha of every pixel without copying the image:
+    /// let pixels = image.get_pixels_view();
+    /// for (let i = 3; i < pixels.length; i += 4) {
+    ///     pixels[i] /= 2;
+    /// }
+    /// ```
+    pub fn get_pixels_view(&mut self) -> Uint8ClampedArray {
+        // Safe as long as wasm memory isn't grown while JS holds the view, which the docs above require
+        unsafe { Uint8ClampedArray::view(&self.raw_pixels) }
+    }
+
+    /// Get ImageData backed by a view over the PhotonImage's pixels in wasm memory, without copying them,
+    /// for drawing with `putImageData`. It's only valid until the next call into photon; see `get_pixels_view`.
+    /// Returns an error if the ImageData can't be created, such as when the image has no pixels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // For example, in JavaScript, to draw the image onto a canvas after filtering it:
+    /// photon.filter(image, "oceanic");
+    /// ctx.putImageData(image.get_image_data_view(), 0, 0);
+    /// ```
+    pub fn get_image_data_view(&mut self) -> Result<ImageData, JsValue> {
+        ImageData::new_with_js_u8_clamped_array_and_sh(&self.get_pixels_view(), self.width, self.height)
+    }
+
+    /// Get a pointer to the PhotonImage's pixels in wasm memory, for creating views with
+    /// `new Uint8ClampedArray(memory.buffer, ptr, len)`. The same lifetime rules apply as for `get_pixels_view`.
+    pub fn get_pixels_ptr(&self) -> *const u8 {
+        self.raw_pixels.as_ptr()
+    }
+
+    /// Get the number of bytes of pixel data, which is `width * height * 4`.
+    pub fn get_pixels_len(&self) -> usize {
+        self.raw_pixels.len()
+    }
+
+    /// Copy pixels into the PhotonImage in place, without allocating a new buffer.
+    /// The pixels must be the same size as the PhotonImage's.
+    pub fn set_pixels(&mut self, pixels: &[u8]) {
+        if pixels.len() != self.raw_pixels.len() {
+            panic!("Expected {} bytes of pixels, but got {}", self.raw_pixels.len(), pixels.len());
+        }
+        self.raw_pixels.copy_from_slice(pixels);
+    }
+
+    /// Copy ImageData of the same size into the PhotonImage in place, with a single copy straight into wasm memory.
+    pub fn update_from_image_data(&mut self, img_data: &ImageData) {
+        if img_data.width() != self.width || img_data.height() != self.height {
+            panic!("The ImageData must be the same size as the PhotonImage");
+        }
+        let data: Uint8ClampedArray = Reflect::get(img_data, &JsValue::from_str("data")).unwrap().unchecked_into();
+        data.copy_to(&mut self.raw_pixels);
+    }
+
     /// Convert ImageData to raw pixels, and update the PhotonImage's raw pixels to this.
     pub fn set_imgdata(&mut self, img_data: ImageData) {
         let width = img_data.width();
@@ -131,7 +198,7 @@ impl From<ImageData> for PhotonImage {
 
 /// RGB color type.
 #[wasm_bindgen]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
 pub struct Rgb {
     r: u8,
     g: u8,
@@ -192,8 +259,11 @@ impl From<Vec<u8>> for Rgb {
 pub fn run() -> Result<(), JsValue> {
     set_panic_hook();
 
-    let window = web_sys::window().expect("No Window found, should have a Window");
-    let document = window.document().expect("No Document found, should have a Document");
+    // Web Workers have no window or document to write to, but WASM is still running
+    let document = match web_sys::window().and_then(|window| window.document()) {
+        Some(document) => document,
+        None => return Ok(()),
+    };
 
     let p: web_sys::Node = document.create_element("p")?.into();
     p.set_text_content(Some("You're successfully running WASM!"));
@@ -239,6 +309,77 @@ pub fn open_image(canvas: HtmlCanvasElement, ctx: CanvasRenderingContext2d) -> P
     return PhotonImage {raw_pixels: raw_pixels, width: canvas.width(), height: canvas.height() }
 }
 
+/// Get the ImageData from an OffscreenCanvas's 2D context. Works in Web Workers.
+#[wasm_bindgen]
+pub fn get_image_data_offscreen(canvas: &OffscreenCanvas, ctx: &OffscreenCanvasRenderingContext2d) -> Result<ImageData, JsValue> {
+    set_panic_hook();
+    ctx.get_image_data(0.0, 0.0, canvas.width() as f64, canvas.height() as f64)
+}
+
+/// Place a PhotonImage onto an OffscreenCanvas's 2D context. Works in Web Workers.
+///
+/// Unlike `putImageData`, the PhotonImage isn't consumed, and its pixels are drawn without copying them into new ImageData first.
+#[wasm_bindgen]
+pub fn put_image_data_offscreen(ctx: &OffscreenCanvasRenderingContext2d, new_image: &mut PhotonImage) -> Result<(), JsValue> {
+    ctx.put_image_data(&new_image.get_image_data_view()?, 0.0, 0.0)
+}
+
+/// Convert an OffscreenCanvas to a PhotonImage. Works in Web Workers.
+///
+/// This converts the ImageData found in the canvas context to a PhotonImage,
+/// which can then have effects or filters applied to it.
+#[wasm_bindgen]
+pub fn open_image_offscreen(canvas: &OffscreenCanvas, ctx: &OffscreenCanvasRenderingContext2d) -> Result<PhotonImage, JsValue> {
+    let imgdata = get_image_data_offscreen(canvas, ctx)?;
+    let raw_pixels = to_raw_pixels(imgdata);
+    Ok(PhotonImage { raw_pixels, width: canvas.width(), height: canvas.height() })
+}
+
+/// Convert an ImageBitmap to a PhotonImage. Works in Web Workers.
+///
+/// ImageBitmaps can be transferred to a worker without copying, so this is the quickest way
+/// to hand an image from the main thread to a worker.
+///
+/// # Example
+///
+/// ```
+/// // For example, in a Web Worker that's posted an ImageBitmap, to filter it and post it back:
+/// onmessage = (event) => {
+///     let image = photon.open_image_bitmap(event.data);
+///     photon.filter(image, "oceanic");
+///     let bitmap = photon.to_image_bitmap(image);
+///     postMessage(bitmap, [bitmap]);
+/// };
+/// ```
+#[wasm_bindgen]
+pub fn open_image_bitmap(bitmap: &ImageBitmap) -> Result<PhotonImage, JsValue> {
+    set_panic_hook();
+    let (canvas, ctx) = offscreen_canvas(bitmap.width(), bitmap.height())?;
+    ctx.draw_image_with_image_bitmap(bitmap, 0.0, 0.0)?;
+    open_image_offscreen(&canvas, &ctx)
+}
+
+/// Convert a PhotonImage to an ImageBitmap, which can be drawn onto a canvas,
+/// or transferred from a Web Worker to the main thread without copying. Works in Web Workers.
+#[wasm_bindgen]
+pub fn to_image_bitmap(photon_image: &mut PhotonImage) -> Result<ImageBitmap, JsValue> {
+    set_panic_hook();
+    let (canvas, ctx) = offscreen_canvas(photon_image.width, photon_image.height)?;
+    put_image_data_offscreen(&ctx, photon_image)?;
+    canvas.transfer_to_image_bitmap()
+}
+
+/// Create an OffscreenCanvas and its 2D context.
+/// Returns an error if OffscreenCanvas isn't supported, or the canvas has no 2D context.
+pub(crate) fn offscreen_canvas(width: u32, height: u32) -> Result<(OffscreenCanvas, OffscreenCanvasRenderingContext2d), JsValue> {
+    let canvas = OffscreenCanvas::new(width, height)?;
+    let ctx = canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("The OffscreenCanvas has no 2D context"))?
+        .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+    Ok((canvas, ctx))
+}
+
 /// Convert ImageData to a raw pixel vec of u8s.
 #[wasm_bindgen]
 pub fn to_raw_pixels(imgdata: ImageData) -> Vec<u8> {
@@ -299,4 +440,14 @@ pub mod colour_spaces;
 pub mod multiple;
 pub mod noise;
 pub mod helpers;
+pub mod quantize;
+pub mod dither;
+pub mod morphology;
+pub mod denoise;
+pub mod textures;
+pub mod distort;
+pub mod lut;
+pub mod mask;
+pub mod document;
+pub mod tiles;
 mod tests;
\ No newline at end of file
@@ -0,0 +1,545 @@
+//! Lookup tables (LUTs), for applying colour grades and tone curves.
+//!
+//! 1D LUTs map each channel through its own curve. 3D LUTs map every colour to a new colour, so they
+//! can capture grades that mix channels, such as those exported by DaVinci Resolve or Adobe tools
+//! in the `.cube` format.
+
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use wasm_bindgen::prelude::*;
+use crate::PhotonImage;
+
+/// Errors from creating a LUT or parsing a `.cube` file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LutError {
+    /// A value isn't a finite number.
+    InvalidNumber { line: usize, word: String },
+    /// A line has the wrong number of values.
+    WrongValueCount { line: usize, expected: usize, found: usize },
+    /// A line starts with a keyword that isn't part of the `.cube` format.
+    UnknownKeyword { line: usize, keyword: String },
+    /// A LUT size isn't a whole number of at least 2.
+    InvalidSize { line: usize, size: String },
+    /// A 3D LUT size is too large for the table to fit in memory.
+    SizeTooLarge(usize),
+    /// The file declares both a 1D and a 3D LUT size.
+    ConflictingSizes,
+    /// The file doesn't declare a size for a LUT of this many dimensions, 1 or 3.
+    MissingSize(usize),
+    /// The domain maximum isn't greater than the minimum for every channel.
+    InvalidDomain,
+    /// The number of entries doesn't match the declared size.
+    WrongEntryCount { expected: usize, found: usize },
+    /// A curve of a 1D LUT has fewer than 2 entries.
+    CurveTooShort,
+    /// A LUT size is less than 2.
+    SizeTooSmall(usize),
+    /// The data for a 3D LUT has the wrong number of values for its size.
+    WrongDataLength { size: usize, expected: usize, found: usize },
+    /// Control points aren't at least 2 finite x and y pairs with increasing x.
+    InvalidControlPoints,
+}
+
+impl fmt::Display for LutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LutError::InvalidNumber { line, word } => write!(f, "Invalid number '{}' on line {} of the .cube file", word, line),
+            LutError::WrongValueCount { line, expected, found } =>
+                write!(f, "Expected {} values on line {} of the .cube file, found {}", expected, line, found),
+            LutError::UnknownKeyword { line, keyword } => write!(f, "Unknown keyword '{}' on line {} of the .cube file", keyword, line),
+            LutError::InvalidSize { line, size } => write!(f, "Invalid LUT size '{}' on line {} of the .cube file", size, line),
+            LutError::SizeTooLarge(size) => write!(f, "A 3D LUT size of {} is too large", size),
+            LutError::ConflictingSizes => write!(f, "The .cube file declares both a 1D and a 3D LUT size"),
+            LutError::MissingSize(dimensions) => write!(f, "The .cube file doesn't contain a {}D LUT", dimensions),
+            LutError::InvalidDomain => write!(f, "The .cube file's domain maximum must be greater than its minimum"),
+            LutError::WrongEntryCount { expected, found } =>
+                write!(f, "The .cube file has {} entries, but its LUT size needs {}", found, expected),
+            LutError::CurveTooShort => write!(f, "Each curve of a 1D LUT needs at least 2 entries"),
+            LutError::SizeTooSmall(size) => write!(f, "A LUT needs a size of at least 2, but {} was given", size),
+            LutError::WrongDataLength { size, expected, found } =>
+                write!(f, "A 3D LUT of size {} needs {} values, but {} were given", size, expected, found),
+            LutError::InvalidControlPoints =>
+                write!(f, "A curve needs at least 2 control points, given as finite x and y pairs with increasing x values"),
+        }
+    }
+}
+
+impl std::error::Error for LutError {}
+
+impl From<LutError> for JsValue {
+    fn from(error: LutError) -> JsValue {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+/// Interpolation methods for 3D LUTs.
+///
+/// Trilinear interpolation blends the 8 lattice points around a colour. Tetrahedral interpolation
+/// blends only 4 of them, which is faster and keeps neutral greys neutral, so most grading tools use it.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum LutInterpolation {
+    Trilinear = 1,
+    Tetrahedral = 2,
+}
+
+/// A 1D LUT, with a separate curve for each of the Red, Green and Blue channels.
+///
+/// Each curve is a list of output values from 0 to 1, for evenly spaced inputs across the LUT's domain.
+/// Inputs between entries are linearly interpolated.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Lut1D {
+    curves: [Vec<f32>; 3],
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+}
+
+#[wasm_bindgen]
+impl Lut1D {
+    #[wasm_bindgen(constructor)]
+    /// Create a 1D LUT from a curve for each channel. Returns an error if any curve has fewer than 2 entries.
+    pub fn new(red: Vec<f32>, green: Vec<f32>, blue: Vec<f32>) -> Result<Lut1D, LutError> {
+        if [&red, &green, &blue].iter().any(|curve| curve.len() < 2) {
+            return Err(LutError::CurveTooShort);
+        }
+        Ok(Lut1D { curves: [red, green, blue], domain_min: [0.0; 3], domain_max: [1.0; 3] })
+    }
+
+    /// Create a 1D LUT that leaves colours unchanged. Returns an error if `size` is less than 2.
+    pub fn identity(size: usize) -> Result<Lut1D, LutError> {
+        Lut1D::from_curve(size, |x| x)
+    }
+
+    /// Parse a 1D LUT from the text of a `.cube` file. Returns an error if the file is malformed.
+    pub fn from_cube(text: &str) -> Result<Lut1D, LutError> {
+        let cube = parse_cube(text)?;
+        if cube.size_1d.is_none() {
+            return Err(LutError::MissingSize(1));
+        }
+        let mut lut = Lut1D::new(
+            cube.values.iter().map(|v| v[0]).collect(),
+            cube.values.iter().map(|v| v[1]).collect(),
+            cube.values.iter().map(|v| v[2]).collect(),
+        )?;
+        lut.domain_min = cube.domain_min;
+        lut.domain_max = cube.domain_max;
+        Ok(lut)
+    }
+
+    /// Parse a 1D LUT from the bytes of a `.cube` file, such as the contents of a `File` read in JavaScript.
+    /// Returns an error if the file is malformed.
+    pub fn from_cube_bytes(bytes: Vec<u8>) -> Result<Lut1D, LutError> {
+        Lut1D::from_cube(&String::from_utf8_lossy(&bytes))
+    }
+
+    /// Create a 1D LUT from tone curves, like those in the curves tool of photo editors.
+    ///
+    /// Each curve is given as a flat list of control points, `[x0, y0, x1, y1, ...]`, from 0 to 1 with
+    /// increasing `x`. The curve passes smoothly through every point without overshooting them.
+    /// Returns an error if a curve has fewer than 2 points, an odd number of values, values that aren't finite,
+    /// or `x` values that don't increase.
+    pub fn from_control_points(red: Vec<f32>, green: Vec<f32>, blue: Vec<f32>) -> Result<Lut1D, LutError> {
+        let curve = |points: &[f32]| -> Result<Vec<f32>, LutError> {
+            let spline = MonotoneSpline::new(points)?;
+            Ok((0..256).map(|i| num::clamp(spline.at(i as f32 / 255.0), 0.0, 1.0)).collect())
+        };
+        Lut1D::new(curve(&red)?, curve(&green)?, curve(&blue)?)
+    }
+
+    /// Get the number of entries in each curve.
+    pub fn get_size(&self) -> usize {
+        self.curves[0].len()
+    }
+}
+
+impl Lut1D {
+    /// Create a 1D LUT that applies the same curve, a function from 0-1 inputs to 0-1 outputs, to every channel.
+    /// Returns an error if `size` is less than 2.
+    pub fn from_curve<F>(size: usize, curve: F) -> Result<Lut1D, LutError> where F: Fn(f32) -> f32 {
+        if size < 2 {
+            return Err(LutError::SizeTooSmall(size));
+        }
+        let values: Vec<f32> = (0..size).map(|i| curve(i as f32 / (size - 1) as f32)).collect();
+        Lut1D::new(values.clone(), values.clone(), values)
+    }
+
+    /// Look up a colour, with channels from 0 to 1.
+    pub fn lookup(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let mut result = [0.0; 3];
+        for (channel, value) in result.iter_mut().enumerate() {
+            let curve = &self.curves[channel];
+            let x = normalize(rgb[channel], self.domain_min[channel], self.domain_max[channel]);
+            let position = x * (curve.len() - 1) as f32;
+            let i = (position.floor() as usize).min(curve.len() - 2);
+            let t = position - i as f32;
+            *value = curve[i] + (curve[i + 1] - curve[i]) * t;
+        }
+        result
+    }
+}
+
+/// A 3D LUT: a lattice of `size` x `size` x `size` output colours, with channels from 0 to 1.
+///
+/// Colours between lattice points are interpolated, tetrahedrally by default.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Lut3D {
+    size: usize,
+    table: Vec<[f32; 3]>,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    interpolation: LutInterpolation,
+}
+
+#[wasm_bindgen]
+impl Lut3D {
+    #[wasm_bindgen(constructor)]
+    /// Create a 3D LUT from a flat list of `size` * `size` * `size` RGB triplets, in the `.cube` order
+    /// where the Red index changes fastest and the Blue index slowest.
+    /// Returns an error if `size` is less than 2, or `data` has the wrong number of values.
+    pub fn new(size: usize, data: Vec<f32>) -> Result<Lut3D, LutError> {
+        let values = value_count(size)?;
+        if data.len() != values {
+            return Err(LutError::WrongDataLength { size, expected: values, found: data.len() });
+        }
+        Ok(Lut3D {
+            size,
+            table: data.chunks(3).map(|v| [v[0], v[1], v[2]]).collect(),
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            interpolation: LutInterpolation::Tetrahedral,
+        })
+    }
+
+    /// Create a 3D LUT that leaves colours unchanged. Returns an error if `size` is less than 2 or too large.
+    pub fn identity(size: usize) -> Result<Lut3D, LutError> {
+        Lut3D::from_fn(size, |rgb| rgb)
+    }
+
+    /// Parse a 3D LUT from the text of a `.cube` file. Returns an error if the file is malformed.
+    pub fn from_cube(text: &str) -> Result<Lut3D, LutError> {
+        let cube = parse_cube(text)?;
+        let size = match cube.size_3d {
+            Some(size) => size,
+            None => return Err(LutError::MissingSize(3)),
+        };
+        let data: Vec<f32> = cube.values.iter().flat_map(|v| v.to_vec()).collect();
+        let mut lut = Lut3D::new(size, data)?;
+        lut.domain_min = cube.domain_min;
+        lut.domain_max = cube.domain_max;
+        Ok(lut)
+    }
+
+    /// Parse a 3D LUT from the bytes of a `.cube` file, such as the contents of a `File` read in JavaScript.
+    /// Returns an error if the file is malformed.
+    pub fn from_cube_bytes(bytes: Vec<u8>) -> Result<Lut3D, LutError> {
+        Lut3D::from_cube(&String::from_utf8_lossy(&bytes))
+    }
+
+    /// Get the number of lattice points along each axis.
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    /// Get the interpolation method.
+    pub fn get_interpolation(&self) -> LutInterpolation {
+        self.interpolation
+    }
+
+    /// Set the interpolation method. Trilinear = 1, Tetrahedral = 2.
+    pub fn set_interpolation(&mut self, interpolation: LutInterpolation) {
+        self.interpolation = interpolation;
+    }
+}
+
+impl Lut3D {
+    /// Create a 3D LUT by sampling a colour transform, a function on colours with channels from 0 to 1.
+    /// Returns an error if `size` is less than 2 or too large.
+    pub fn from_fn<F>(size: usize, transform: F) -> Result<Lut3D, LutError> where F: Fn([f32; 3]) -> [f32; 3] {
+        let step = 1.0 / (size.max(2) - 1) as f32;
+        let mut data = Vec::with_capacity(value_count(size)?);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.extend_from_slice(&transform([r as f32 * step, g as f32 * step, b as f32 * step]));
+                }
+            }
+        }
+        Lut3D::new(size, data)
+    }
+
+    fn at(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[(b * self.size + g) * self.size + r]
+    }
+
+    /// Look up a colour, with channels from 0 to 1.
+    pub fn lookup(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let last = self.size - 1;
+        let mut base = [0usize; 3];
+        let mut fraction = [0.0f32; 3];
+        for channel in 0..3 {
+            let x = normalize(rgb[channel], self.domain_min[channel], self.domain_max[channel]);
+            let position = x * last as f32;
+            base[channel] = (position.floor() as usize).min(last - 1);
+            fraction[channel] = position - base[channel] as f32;
+        }
+        let [r, g, b] = base;
+        let [fr, fg, fb] = fraction;
+        let corner = |dr: usize, dg: usize, db: usize| self.at(r + dr, g + dg, b + db);
+
+        let mut result = [0.0; 3];
+        match self.interpolation {
+            LutInterpolation::Trilinear => {
+                let corners = [
+                    corner(0, 0, 0), corner(1, 0, 0), corner(0, 1, 0), corner(1, 1, 0),
+                    corner(0, 0, 1), corner(1, 0, 1), corner(0, 1, 1), corner(1, 1, 1),
+                ];
+                for (channel, value) in result.iter_mut().enumerate() {
+                    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+                    let c = |i: usize| corners[i][channel];
+                    let front = lerp(lerp(c(0), c(1), fr), lerp(c(2), c(3), fr), fg);
+                    let back = lerp(lerp(c(4), c(5), fr), lerp(c(6), c(7), fr), fg);
+                    *value = lerp(front, back, fb);
+                }
+            }
+            LutInterpolation::Tetrahedral => {
+                // Walk from the nearest corner to the far corner, one axis at a time, in order of largest fraction
+                let (c000, c111) = (corner(0, 0, 0), corner(1, 1, 1));
+                let (first, second, w1, w2, w3) = if fr > fg {
+                    if fg > fb {
+                        (corner(1, 0, 0), corner(1, 1, 0), fr, fg, fb)
+                    } else if fr > fb {
+                        (corner(1, 0, 0), corner(1, 0, 1), fr, fb, fg)
+                    } else {
+                        (corner(0, 0, 1), corner(1, 0, 1), fb, fr, fg)
+                    }
+                } else if fb > fg {
+                    (corner(0, 0, 1), corner(0, 1, 1), fb, fg, fr)
+                } else if fb > fr {
+                    (corner(0, 1, 0), corner(0, 1, 1), fg, fb, fr)
+                } else {
+                    (corner(0, 1, 0), corner(1, 1, 0), fg, fr, fb)
+                };
+                for (channel, value) in result.iter_mut().enumerate() {
+                    *value = c000[channel]
+                        + w1 * (first[channel] - c000[channel])
+                        + w2 * (second[channel] - first[channel])
+                        + w3 * (c111[channel] - second[channel]);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// A monotone cubic (Fritsch-Carlson) spline through a set of control points.
+struct MonotoneSpline {
+    points: Vec<(f32, f32)>,
+    tangents: Vec<f32>,
+}
+
+impl MonotoneSpline {
+    fn new(flat_points: &[f32]) -> Result<MonotoneSpline, LutError> {
+        if flat_points.len() < 4 || flat_points.len() % 2 == 1 || flat_points.iter().any(|value| !value.is_finite()) {
+            return Err(LutError::InvalidControlPoints);
+        }
+        let points: Vec<(f32, f32)> = flat_points.chunks(2).map(|p| (p[0], p[1])).collect();
+        if points.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(LutError::InvalidControlPoints);
+        }
+
+        let n = points.len();
+        let slopes: Vec<f32> = points.windows(2).map(|pair| (pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0)).collect();
+        let mut tangents = vec![0.0; n];
+        tangents[0] = slopes[0];
+        tangents[n - 1] = slopes[n - 2];
+        for i in 1..n - 1 {
+            if slopes[i - 1] * slopes[i] > 0.0 {
+                tangents[i] = (slopes[i - 1] + slopes[i]) / 2.0;
+            }
+        }
+        // Limit the tangents so that the curve doesn't overshoot between points
+        for (i, slope) in slopes.iter().enumerate() {
+            if *slope == 0.0 {
+                tangents[i] = 0.0;
+                tangents[i + 1] = 0.0;
+                continue;
+            }
+            let (a, b) = (tangents[i] / slope, tangents[i + 1] / slope);
+            let magnitude = a * a + b * b;
+            if magnitude > 9.0 {
+                let t = 3.0 / magnitude.sqrt();
+                tangents[i] = t * a * slope;
+                tangents[i + 1] = t * b * slope;
+            }
+        }
+        Ok(MonotoneSpline { points, tangents })
+    }
+
+    fn at(&self, x: f32) -> f32 {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        if x <= first.0 {
+            return first.1;
+        }
+        if x >= last.0 {
+            return last.1;
+        }
+        let i = self.points.windows(2).position(|pair| x < pair[1].0).unwrap();
+        let ((x0, y0), (x1, y1)) = (self.points[i], self.points[i + 1]);
+        let h = x1 - x0;
+        let t = (x - x0) / h;
+        let (t2, t3) = (t * t, t * t * t);
+        (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+            + (t3 - 2.0 * t2 + t) * h * self.tangents[i]
+            + (-2.0 * t3 + 3.0 * t2) * y1
+            + (t3 - t2) * h * self.tangents[i + 1]
+    }
+}
+
+/// The number of entries in a 3D LUT of a given size, or `None` if it overflows.
+fn entry_count(size: usize) -> Option<usize> {
+    size.checked_mul(size).and_then(|square| square.checked_mul(size))
+}
+
+/// The number of values in the table of a 3D LUT of a given size, checking that the size is valid.
+fn value_count(size: usize) -> Result<usize, LutError> {
+    if size < 2 {
+        return Err(LutError::SizeTooSmall(size));
+    }
+    entry_count(size).and_then(|entries| entries.checked_mul(3)).ok_or(LutError::SizeTooLarge(size))
+}
+
+/// Map a value from a LUT's domain onto 0 to 1.
+fn normalize(value: f32, min: f32, max: f32) -> f32 {
+    num::clamp((value - min) / (max - min), 0.0, 1.0)
+}
+
+/// The contents of a `.cube` file.
+struct Cube {
+    size_1d: Option<usize>,
+    size_3d: Option<usize>,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    values: Vec<[f32; 3]>,
+}
+
+/// Parse the text of a `.cube` file, as specified by Adobe and extended by DaVinci Resolve.
+fn parse_cube(text: &str) -> Result<Cube, LutError> {
+    let mut cube = Cube { size_1d: None, size_3d: None, domain_min: [0.0; 3], domain_max: [1.0; 3], values: vec![] };
+
+    let parse_floats = |line: usize, words: &[&str], expected: usize| -> Result<Vec<f32>, LutError> {
+        if words.len() != expected {
+            return Err(LutError::WrongValueCount { line, expected, found: words.len() });
+        }
+        words.iter().map(|word| match word.parse::<f32>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(LutError::InvalidNumber { line, word: word.to_string() }),
+        }).collect()
+    };
+    let parse_triplet = |line: usize, words: &[&str]| -> Result<[f32; 3], LutError> {
+        let values = parse_floats(line, words, 3)?;
+        Ok([values[0], values[1], values[2]])
+    };
+    let parse_size = |line: usize, words: &[&str]| -> Result<usize, LutError> {
+        match words {
+            [size] => match size.parse::<usize>() {
+                Ok(size) if size >= 2 => Ok(size),
+                _ => Err(LutError::InvalidSize { line, size: size.to_string() }),
+            },
+            _ => Err(LutError::WrongValueCount { line, expected: 1, found: words.len() }),
+        }
+    };
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("TITLE") {
+            continue;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (keyword, rest) = (words[0], &words[1..]);
+        match keyword {
+            "LUT_1D_SIZE" => cube.size_1d = Some(parse_size(line_number, rest)?),
+            "LUT_3D_SIZE" => cube.size_3d = Some(parse_size(line_number, rest)?),
+            "DOMAIN_MIN" => cube.domain_min = parse_triplet(line_number, rest)?,
+            "DOMAIN_MAX" => cube.domain_max = parse_triplet(line_number, rest)?,
+            // Resolve writes the domain as a single range shared by all channels
+            "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {
+                let range = parse_floats(line_number, rest, 2)?;
+                cube.domain_min = [range[0]; 3];
+                cube.domain_max = [range[1]; 3];
+            }
+            _ if keyword.parse::<f32>().is_err() && keyword.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') => {
+                return Err(LutError::UnknownKeyword { line: line_number, keyword: keyword.to_string() });
+            }
+            _ => cube.values.push(parse_triplet(line_number, &words)?),
+        }
+    }
+
+    let expected = match (cube.size_1d, cube.size_3d) {
+        (Some(_), Some(_)) => return Err(LutError::ConflictingSizes),
+        (Some(size), None) => Some(size),
+        (None, Some(size)) => Some(entry_count(size).ok_or(LutError::SizeTooLarge(size))?),
+        (None, None) => None,
+    };
+    for channel in 0..3 {
+        if cube.domain_max[channel] <= cube.domain_min[channel] {
+            return Err(LutError::InvalidDomain);
+        }
+    }
+    if let Some(expected) = expected {
+        if cube.values.len() != expected {
+            return Err(LutError::WrongEntryCount { expected, found: cube.values.len() });
+        }
+    }
+    Ok(cube)
+}
+
+/// Run every pixel of an image through a LUT lookup, leaving the alpha channel untouched.
+fn apply<F>(photon_image: &mut PhotonImage, lookup: F) where F: Fn([f32; 3]) -> [f32; 3] {
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        let rgb = lookup([px[0] as f32 / 255.0, px[1] as f32 / 255.0, px[2] as f32 / 255.0]);
+        for channel in 0..3 {
+            px[channel] = num::clamp((rgb[channel] * 255.0).round(), 0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// Apply a 3D LUT to an image, such as a colour grade loaded from a `.cube` file.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `lut` - The 3D LUT.
+///
+/// # Example
+///
+/// ```
+/// // For example, to apply a grade exported from a colour grading tool:
+/// use photon::lut::{apply_lut, Lut3D};
+/// let lut = Lut3D::from_cube(&std::fs::read_to_string("grade.cube").unwrap()).unwrap();
+/// apply_lut(&mut img, &lut);
+/// ```
+#[wasm_bindgen]
+pub fn apply_lut(photon_image: &mut PhotonImage, lut: &Lut3D) {
+    apply(photon_image, |rgb| lut.lookup(rgb));
+}
+
+/// Apply a 1D LUT to an image, mapping each channel through its curve.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `lut` - The 1D LUT.
+///
+/// # Example
+///
+/// ```
+/// // For example, to apply a gamma curve:
+/// use photon::lut::{apply_lut_1d, Lut1D};
+/// let lut = Lut1D::from_curve(256, |x| x.powf(1.0 / 2.2)).unwrap();
+/// apply_lut_1d(&mut img, &lut);
+/// ```
+#[wasm_bindgen]
+pub fn apply_lut_1d(photon_image: &mut PhotonImage, lut: &Lut1D) {
+    apply(photon_image, |rgb| lut.lookup(rgb));
+}
@@ -0,0 +1,246 @@
+//! Apply operations to part of an image, inside a rectangle or weighted by a greyscale mask.
+//!
+//! In Rust, `apply_in_rect` and `apply_with_mask` wrap any operation on a PhotonImage. In JavaScript, apply
+//! the operation to a copy of the image, then use `blend_in_rect` or `blend_with_mask` to combine it with the original.
+
+use wasm_bindgen::prelude::*;
+use crate::PhotonImage;
+
+/// The weight of a mask pixel, from 0 to 1: its luma, scaled by its alpha.
+pub(crate) fn mask_weight(px: &[u8]) -> f32 {
+    let luma = 0.2126 * px[0] as f32 + 0.7152 * px[1] as f32 + 0.0722 * px[2] as f32;
+    luma / 255.0 * px[3] as f32 / 255.0
+}
+
+/// Check that a rectangle lies inside an image.
+fn check_rect(photon_image: &PhotonImage, x: u32, y: u32, width: u32, height: u32) {
+    // Huge coordinates from JavaScript could overflow, so treat an overflow as out of range
+    let fits = |start: u32, length: u32, limit: u32| match start.checked_add(length) {
+        Some(end) => end <= limit,
+        None => false,
+    };
+    if !fits(x, width, photon_image.width) || !fits(y, height, photon_image.height) {
+        panic!("The rectangle at ({}, {}) of size {}x{} doesn't fit inside the {}x{} image",
+               x, y, width, height, photon_image.width, photon_image.height);
+    }
+}
+
+/// Copy a rectangle of one image into another image of the same size.
+fn copy_rect(target: &mut PhotonImage, source: &PhotonImage, x: u32, y: u32, width: u32, height: u32) {
+    for row in y..y + height {
+        let start = ((row * source.width + x) * 4) as usize;
+        let end = start + (width * 4) as usize;
+        target.raw_pixels[start..end].copy_from_slice(&source.raw_pixels[start..end]);
+    }
+}
+
+/// Blend an edited copy of an image into the original, weighted by a greyscale mask.
+///
+/// White areas of the mask take the edited image, black areas keep the original, and greys mix the two.
+/// Transparent areas of the mask also keep the original, so masks painted onto a transparent canvas work as expected.
+///
+/// # Arguments
+/// * `photon_image` - The original image, which is updated in place.
+/// * `edited` - The edited image. Must be the same size as the original.
+/// * `mask` - The mask. Must be the same size as the original.
+///
+/// # Example
+///
+/// ```
+/// // For example, to desaturate only the areas painted white in a mask:
+/// use photon::mask::blend_with_mask;
+/// use photon::monochrome::grayscale;
+/// let mut edited = PhotonImage::new(img.get_raw_pixels(), img.get_width(), img.get_height());
+/// grayscale(&mut edited);
+/// blend_with_mask(&mut img, &edited, &mask);
+/// ```
+#[wasm_bindgen]
+pub fn blend_with_mask(photon_image: &mut PhotonImage, edited: &PhotonImage, mask: &PhotonImage) {
+    for other in [edited, mask].iter() {
+        if other.width != photon_image.width || other.height != photon_image.height {
+            panic!("The edited image and mask must be the same size as the original image");
+        }
+    }
+    let pixels = photon_image.raw_pixels.chunks_mut(4).zip(edited.raw_pixels.chunks(4)).zip(mask.raw_pixels.chunks(4));
+    for ((px, edited_px), mask_px) in pixels {
+        let weight = mask_weight(mask_px);
+        for (value, edited_value) in px.iter_mut().zip(edited_px.iter()) {
+            *value = (*value as f32 + (*edited_value as f32 - *value as f32) * weight).round() as u8;
+        }
+    }
+}
+
+/// Copy a rectangle of an edited copy of an image into the original.
+///
+/// # Arguments
+/// * `photon_image` - The original image, which is updated in place.
+/// * `edited` - The edited image. Must be the same size as the original.
+/// * `x` - The x-coordinate of the rectangle's top-left corner.
+/// * `y` - The y-coordinate of the rectangle's top-left corner.
+/// * `width` - The width of the rectangle.
+/// * `height` - The height of the rectangle.
+///
+/// # Example
+///
+/// ```
+/// // For example, to invert only a 100x50 area of an image:
+/// use photon::mask::blend_in_rect;
+/// use photon::channels::invert;
+/// let mut edited = PhotonImage::new(img.get_raw_pixels(), img.get_width(), img.get_height());
+/// invert(&mut edited);
+/// blend_in_rect(&mut img, &edited, 20, 40, 100, 50);
+/// ```
+#[wasm_bindgen]
+pub fn blend_in_rect(photon_image: &mut PhotonImage, edited: &PhotonImage, x: u32, y: u32, width: u32, height: u32) {
+    if edited.width != photon_image.width || edited.height != photon_image.height {
+        panic!("The edited image must be the same size as the original image");
+    }
+    check_rect(photon_image, x, y, width, height);
+    copy_rect(photon_image, edited, x, y, width, height);
+}
+
+/// Apply any operation to an image, weighted by a greyscale mask. See `blend_with_mask` for how the mask is used.
+///
+/// The operation sees the whole image, so effects that depend on neighbouring pixels or the image's size,
+/// such as blurs and vignettes, behave the same as without a mask.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `mask` - The mask. Must be the same size as the image.
+/// * `operation` - The operation to apply. It mustn't change the size of the image.
+///
+/// # Example
+///
+/// ```
+/// // For example, to brighten the sky with a gradient mask that fades out towards the middle of the image:
+/// use photon::mask::{apply_with_mask, linear_gradient_mask};
+/// use photon::effects::brightness;
+/// let mask = linear_gradient_mask(img.get_width(), img.get_height(), 0.0, 0.0, 0.0, img.get_height() as f32 / 2.0);
+/// apply_with_mask(&mut img, &mask, |img| brightness(img, 0.2));
+/// ```
+pub fn apply_with_mask<F>(photon_image: &mut PhotonImage, mask: &PhotonImage, operation: F) where F: FnOnce(&mut PhotonImage) {
+    let mut edited = photon_image.clone();
+    operation(&mut edited);
+    if edited.width != photon_image.width || edited.height != photon_image.height {
+        panic!("Operations applied with a mask mustn't change the size of the image");
+    }
+    blend_with_mask(photon_image, &edited, mask);
+}
+
+/// Apply any operation to a rectangle of an image, leaving the rest of the image untouched.
+///
+/// The operation is given only the pixels inside the rectangle, as an image of the rectangle's size,
+/// so effects that depend on the image's size, such as vignettes, are centred on the rectangle.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `x` - The x-coordinate of the rectangle's top-left corner.
+/// * `y` - The y-coordinate of the rectangle's top-left corner.
+/// * `width` - The width of the rectangle.
+/// * `height` - The height of the rectangle.
+/// * `operation` - The operation to apply. It mustn't change the size of the image.
+///
+/// # Example
+///
+/// ```
+/// // For example, to blur a face at a known position:
+/// use photon::mask::apply_in_rect;
+/// use photon::conv::gaussian_blur;
+/// apply_in_rect(&mut img, 120, 80, 64, 64, gaussian_blur);
+/// ```
+pub fn apply_in_rect<F>(photon_image: &mut PhotonImage, x: u32, y: u32, width: u32, height: u32, operation: F)
+    where F: FnOnce(&mut PhotonImage) {
+    check_rect(photon_image, x, y, width, height);
+    let mut region = Vec::with_capacity((width * height * 4) as usize);
+    for row in y..y + height {
+        let start = ((row * photon_image.width + x) * 4) as usize;
+        region.extend_from_slice(&photon_image.raw_pixels[start..start + (width * 4) as usize]);
+    }
+
+    let mut region = PhotonImage::new(region, width, height);
+    operation(&mut region);
+    if region.width != width || region.height != height {
+        panic!("Operations applied to a rectangle mustn't change the size of the image");
+    }
+
+    for (i, row) in (y..y + height).enumerate() {
+        let start = ((row * photon_image.width + x) * 4) as usize;
+        let region_start = i * (width * 4) as usize;
+        photon_image.raw_pixels[start..start + (width * 4) as usize]
+            .copy_from_slice(&region.raw_pixels[region_start..region_start + (width * 4) as usize]);
+    }
+}
+
+/// Create a linear gradient mask, which fades from white at a start point to black at an end point.
+///
+/// # Arguments
+/// * `width` - The width of the mask.
+/// * `height` - The height of the mask.
+/// * `start_x` - The x-coordinate where the mask is fully white.
+/// * `start_y` - The y-coordinate where the mask is fully white.
+/// * `end_x` - The x-coordinate where the mask becomes fully black.
+/// * `end_y` - The y-coordinate where the mask becomes fully black.
+///
+/// # Example
+///
+/// ```
+/// // For example, to create a graduated filter mask covering the top half of an image:
+/// use photon::mask::linear_gradient_mask;
+/// let mask = linear_gradient_mask(800, 600, 0.0, 0.0, 0.0, 300.0);
+/// ```
+#[wasm_bindgen]
+pub fn linear_gradient_mask(width: u32, height: u32, start_x: f32, start_y: f32, end_x: f32, end_y: f32) -> PhotonImage {
+    let (dx, dy) = (end_x - start_x, end_y - start_y);
+    let length_sq = dx * dx + dy * dy;
+    if length_sq == 0.0 {
+        panic!("The start and end points of a gradient must be different");
+    }
+    mask_from_fn(width, height, |x, y| {
+        // Project each pixel onto the line from start to end
+        let t = ((x - start_x) * dx + (y - start_y) * dy) / length_sq;
+        1.0 - num::clamp(t, 0.0, 1.0)
+    })
+}
+
+/// Create a radial gradient mask, which is white inside a circle and fades to black outside it.
+///
+/// # Arguments
+/// * `width` - The width of the mask.
+/// * `height` - The height of the mask.
+/// * `centre_x` - The x-coordinate of the centre of the circle.
+/// * `centre_y` - The y-coordinate of the centre of the circle.
+/// * `radius` - The radius of the fully white circle, in pixels.
+/// * `feather` - The width of the fade from white to black beyond the radius, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to create a soft spotlight mask around a subject:
+/// use photon::mask::radial_gradient_mask;
+/// let mask = radial_gradient_mask(800, 600, 400.0, 250.0, 120.0, 80.0);
+/// ```
+#[wasm_bindgen]
+pub fn radial_gradient_mask(width: u32, height: u32, centre_x: f32, centre_y: f32, radius: f32, feather: f32) -> PhotonImage {
+    mask_from_fn(width, height, |x, y| {
+        let distance = ((x - centre_x).powi(2) + (y - centre_y).powi(2)).sqrt();
+        if distance <= radius {
+            1.0
+        } else if feather <= 0.0 {
+            0.0
+        } else {
+            1.0 - num::clamp((distance - radius) / feather, 0.0, 1.0)
+        }
+    })
+}
+
+/// Create a mask from a function giving the weight, from 0 to 1, at the centre of each pixel.
+fn mask_from_fn<F>(width: u32, height: u32, weight: F) -> PhotonImage where F: Fn(f32, f32) -> f32 {
+    let mut raw_pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let v = (weight(x as f32 + 0.5, y as f32 + 0.5) * 255.0).round() as u8;
+            raw_pixels.extend_from_slice(&[v, v, v, 255]);
+        }
+    }
+    PhotonImage::new(raw_pixels, width, height)
+}
@@ -401,7 +401,7 @@ pub fn threshold(mut photon_image: &mut PhotonImage, threshold: u32) {
             let g: f32 = px.data[1].into();
             let b: f32 = px.data[2].into();
 
-            let mut v = 0.2126 * r + 0.7152 * g + 0.072 * b;
+            let mut v = 0.2126 * r + 0.7152 * g + 0.0722 * b;
 
             if v >= threshold as f32 {
                 v = 255.0;
@@ -418,4 +418,270 @@ pub fn threshold(mut photon_image: &mut PhotonImage, threshold: u32) {
     }
     let raw_pixels = img.raw_pixels();
     photon_image.raw_pixels = raw_pixels;
-}
\ No newline at end of file
+}
+
+/// Local thresholding methods for `adaptive_threshold`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdaptiveMethod {
+    Mean = 1,
+    Gaussian = 2,
+}
+
+/// Get the luma value of each pixel in an image, using the Rec. 709 weights.
+fn luma_plane(photon_image: &PhotonImage) -> Vec<f64> {
+    photon_image.raw_pixels.chunks(4)
+        .map(|px| 0.2126 * px[0] as f64 + 0.7152 * px[1] as f64 + 0.0722 * px[2] as f64)
+        .collect()
+}
+
+/// Set each pixel to white if its luma is at or above its threshold, and black otherwise. Alpha is left untouched.
+fn binarize(photon_image: &mut PhotonImage, luma: &[f64], thresholds: &[f64]) {
+    for (i, px) in photon_image.raw_pixels.chunks_mut(4).enumerate() {
+        let v = if luma[i] >= thresholds[i] { 255 } else { 0 };
+        px[0] = v;
+        px[1] = v;
+        px[2] = v;
+    }
+}
+
+/// Build a greyscale image from per-pixel thresholds.
+fn threshold_map(thresholds: &[f64], width: u32, height: u32) -> PhotonImage {
+    let mut raw_pixels = Vec::with_capacity(thresholds.len() * 4);
+    for t in thresholds {
+        let v = num::clamp(t.round(), 0.0, 255.0) as u8;
+        raw_pixels.extend_from_slice(&[v, v, v, 255]);
+    }
+    PhotonImage::new(raw_pixels, width, height)
+}
+
+fn check_block_size(block_size: u32) {
+    if block_size < 3 || block_size % 2 != 1 {
+        panic!("The block size must be an odd number of at least 3");
+    }
+}
+
+/// Get the mean and standard deviation of the `block_size` x `block_size` neighbourhood of every pixel,
+/// using summed-area tables. Neighbourhoods are clipped at the image's edges.
+fn local_statistics(plane: &[f64], width: u32, height: u32, block_size: u32) -> (Vec<f64>, Vec<f64>) {
+    let (w, h) = (width as usize, height as usize);
+    let stride = w + 1;
+    let mut sum = vec![0.0; stride * (h + 1)];
+    let mut sum_sq = vec![0.0; stride * (h + 1)];
+
+    for y in 0..h {
+        let mut row = 0.0;
+        let mut row_sq = 0.0;
+        for x in 0..w {
+            let v = plane[y * w + x];
+            row += v;
+            row_sq += v * v;
+            sum[(y + 1) * stride + x + 1] = sum[y * stride + x + 1] + row;
+            sum_sq[(y + 1) * stride + x + 1] = sum_sq[y * stride + x + 1] + row_sq;
+        }
+    }
+
+    let radius = (block_size / 2) as usize;
+    let mut means = Vec::with_capacity(w * h);
+    let mut deviations = Vec::with_capacity(w * h);
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(radius), (y + radius + 1).min(h));
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(radius), (x + radius + 1).min(w));
+            let area = ((x1 - x0) * (y1 - y0)) as f64;
+            let total = sum[y1 * stride + x1] - sum[y0 * stride + x1] - sum[y1 * stride + x0] + sum[y0 * stride + x0];
+            let total_sq = sum_sq[y1 * stride + x1] - sum_sq[y0 * stride + x1] - sum_sq[y1 * stride + x0] + sum_sq[y0 * stride + x0];
+            let mean = total / area;
+            means.push(mean);
+            deviations.push((total_sq / area - mean * mean).max(0.0).sqrt());
+        }
+    }
+    (means, deviations)
+}
+
+/// Get the Gaussian-weighted mean of the `block_size` x `block_size` neighbourhood of every pixel.
+/// Edges are handled by renormalising the weights of the pixels that fall inside the image.
+fn gaussian_means(plane: &[f64], width: u32, height: u32, block_size: u32) -> Vec<f64> {
+    let (w, h) = (width as usize, height as usize);
+    let radius = (block_size / 2) as isize;
+    // The same sigma OpenCV derives from the block size
+    let sigma = 0.3 * ((block_size as f64 - 1.0) * 0.5 - 1.0) + 0.8;
+    let kernel: Vec<f64> = (-radius..=radius).map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp()).collect();
+
+    let blur = |input: &[f64], horizontal: bool| -> Vec<f64> {
+        let mut output = vec![0.0; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                let mut total = 0.0;
+                let mut weights = 0.0;
+                for (k, weight) in kernel.iter().enumerate() {
+                    let offset = k as isize - radius;
+                    let (sx, sy) = if horizontal { (x as isize + offset, y as isize) } else { (x as isize, y as isize + offset) };
+                    if sx >= 0 && sx < w as isize && sy >= 0 && sy < h as isize {
+                        total += input[sy as usize * w + sx as usize] * weight;
+                        weights += weight;
+                    }
+                }
+                output[y * w + x] = total / weights;
+            }
+        }
+        output
+    };
+    blur(&blur(plane, true), false)
+}
+
+/// Threshold an image using Otsu's method, which picks the global threshold that best separates
+/// the image's luma histogram into two classes.
+///
+/// Pixels with a luma at or above the chosen threshold become white, and all others become black, as in `threshold`.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+///
+/// Returns the chosen threshold, from 0 to 255.
+/// # Example
+///
+/// ```
+/// // For example, to binarize a scanned page and find the threshold used:
+/// use photon::monochrome;
+/// let threshold = monochrome::otsu_threshold(&mut img);
+/// ```
+#[wasm_bindgen]
+pub fn otsu_threshold(photon_image: &mut PhotonImage) -> u32 {
+    let luma = luma_plane(photon_image);
+    let mut histogram = [0u64; 256];
+    for v in luma.iter() {
+        histogram[num::clamp(v.round(), 0.0, 255.0) as usize] += 1;
+    }
+
+    let total = luma.len() as f64;
+    let sum_all: f64 = histogram.iter().enumerate().map(|(i, count)| i as f64 * *count as f64).sum();
+
+    let mut best_threshold = 0;
+    let mut best_variance = -1.0;
+    let mut background_weight = 0.0;
+    let mut background_sum = 0.0;
+
+    // Pixels at or below `level` form the background class
+    for (level, count) in histogram.iter().enumerate() {
+        background_weight += *count as f64;
+        background_sum += level as f64 * *count as f64;
+        let foreground_weight = total - background_weight;
+        if background_weight == 0.0 || foreground_weight == 0.0 {
+            continue;
+        }
+        let background_mean = background_sum / background_weight;
+        let foreground_mean = (sum_all - background_sum) / foreground_weight;
+        let variance = background_weight * foreground_weight * (background_mean - foreground_mean).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = level as u32 + 1;
+        }
+    }
+
+    // An image with a single luma value has no second class, so keep everything white
+    let thresholds = vec![best_threshold as f64 - 0.5; luma.len()];
+    binarize(photon_image, &luma, &thresholds);
+    best_threshold
+}
+
+/// Threshold an image against the average of each pixel's neighbourhood, minus a constant.
+///
+/// Unlike a global threshold, this copes with uneven lighting, such as shadows on photographed documents.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `method` - How the neighbourhood is averaged. Mean = 1, Gaussian = 2.
+/// * `block_size` - The width of the square neighbourhood, in pixels. Must be odd and at least 3.
+/// * `c` - A constant subtracted from the neighbourhood average. Positive values turn more of the page white.
+///
+/// Returns the per-pixel thresholds as a greyscale image.
+/// # Example
+///
+/// ```
+/// // For example, to binarize a scanned page with a 15 pixel Gaussian neighbourhood:
+/// use photon::monochrome::{adaptive_threshold, AdaptiveMethod};
+/// let thresholds = adaptive_threshold(&mut img, AdaptiveMethod::Gaussian, 15, 5.0);
+/// ```
+#[wasm_bindgen]
+pub fn adaptive_threshold(photon_image: &mut PhotonImage, method: AdaptiveMethod, block_size: u32, c: f32) -> PhotonImage {
+    check_block_size(block_size);
+    let (width, height) = (photon_image.width, photon_image.height);
+    let luma = luma_plane(photon_image);
+
+    let means = match method {
+        AdaptiveMethod::Mean => local_statistics(&luma, width, height, block_size).0,
+        AdaptiveMethod::Gaussian => gaussian_means(&luma, width, height, block_size),
+    };
+    let thresholds: Vec<f64> = means.iter().map(|mean| mean - c as f64).collect();
+
+    binarize(photon_image, &luma, &thresholds);
+    threshold_map(&thresholds, width, height)
+}
+
+/// Binarize an image using Sauvola's method, which is designed for documents with stains and uneven backgrounds.
+///
+/// Each pixel's threshold is `mean * (1 + k * (deviation / 128 - 1))`, computed over its neighbourhood,
+/// so low-contrast areas such as blank paper fall to white.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `block_size` - The width of the square neighbourhood, in pixels. Must be odd and at least 3.
+/// * `k` - How strongly the local contrast lowers the threshold. Values from 0.2 to 0.5 are typical.
+///
+/// Returns the per-pixel thresholds as a greyscale image.
+/// # Example
+///
+/// ```
+/// // For example, to binarize a scanned page:
+/// use photon::monochrome;
+/// let thresholds = monochrome::sauvola_threshold(&mut img, 25, 0.34);
+/// ```
+#[wasm_bindgen]
+pub fn sauvola_threshold(photon_image: &mut PhotonImage, block_size: u32, k: f32) -> PhotonImage {
+    check_block_size(block_size);
+    let (width, height) = (photon_image.width, photon_image.height);
+    let luma = luma_plane(photon_image);
+
+    let (means, deviations) = local_statistics(&luma, width, height, block_size);
+    let thresholds: Vec<f64> = means.iter()
+        .zip(deviations.iter())
+        .map(|(mean, deviation)| mean * (1.0 + k as f64 * (deviation / 128.0 - 1.0)))
+        .collect();
+
+    binarize(photon_image, &luma, &thresholds);
+    threshold_map(&thresholds, width, height)
+}
+
+/// Binarize an image using Niblack's method.
+///
+/// Each pixel's threshold is `mean + k * deviation`, computed over its neighbourhood.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `block_size` - The width of the square neighbourhood, in pixels. Must be odd and at least 3.
+/// * `k` - How strongly the local standard deviation shifts the threshold. -0.2 is typical for dark text on light paper.
+///
+/// Returns the per-pixel thresholds as a greyscale image.
+/// # Example
+///
+/// ```
+/// // For example, to binarize a scanned page:
+/// use photon::monochrome;
+/// let thresholds = monochrome::niblack_threshold(&mut img, 25, -0.2);
+/// ```
+#[wasm_bindgen]
+pub fn niblack_threshold(photon_image: &mut PhotonImage, block_size: u32, k: f32) -> PhotonImage {
+    check_block_size(block_size);
+    let (width, height) = (photon_image.width, photon_image.height);
+    let luma = luma_plane(photon_image);
+
+    let (means, deviations) = local_statistics(&luma, width, height, block_size);
+    let thresholds: Vec<f64> = means.iter()
+        .zip(deviations.iter())
+        .map(|(mean, deviation)| mean + k as f64 * deviation)
+        .collect();
+
+    binarize(photon_image, &luma, &thresholds);
+    threshold_map(&thresholds, width, height)
+}
@@ -0,0 +1,220 @@
+//! Morphological operations, such as erosion, dilation, opening and closing.
+//!
+//! All operations work on each of the R, G and B channels independently, so they apply to
+//! binary images (such as the output of `monochrome::threshold`) as well as to greyscale and colour images.
+//! The alpha channel is left untouched.
+
+use wasm_bindgen::prelude::*;
+use crate::PhotonImage;
+
+/// Structuring element shapes.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StructuringElement {
+    Square = 1,
+    Cross = 2,
+    Disk = 3,
+}
+
+impl StructuringElement {
+    /// The pixel offsets covered by the element at a given radius.
+    fn offsets(self, radius: u32) -> Vec<(i32, i32)> {
+        let r = radius as i32;
+        let mut offsets = vec![];
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let inside = match self {
+                    StructuringElement::Square => true,
+                    StructuringElement::Cross => dx == 0 || dy == 0,
+                    StructuringElement::Disk => dx * dx + dy * dy <= r * r,
+                };
+                if inside {
+                    offsets.push((dx, dy));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+/// Replace each channel value with the minimum (erosion) or maximum (dilation) value under the structuring element.
+/// Parts of the element that fall outside the image are ignored.
+fn rank_filter(photon_image: &PhotonImage, shape: StructuringElement, radius: u32, maximum: bool) -> Vec<u8> {
+    let width = photon_image.width as i32;
+    let height = photon_image.height as i32;
+    let offsets = shape.offsets(radius);
+    let input = &photon_image.raw_pixels;
+    let mut output = input.clone();
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = ((y * width + x) * 4) as usize;
+            let mut values = if maximum { [0u8; 3] } else { [255u8; 3] };
+
+            for (dx, dy) in offsets.iter() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || nx >= width || ny < 0 || ny >= height {
+                    continue;
+                }
+                let j = ((ny * width + nx) * 4) as usize;
+                for (channel, value) in values.iter_mut().enumerate() {
+                    *value = if maximum { (*value).max(input[j + channel]) } else { (*value).min(input[j + channel]) };
+                }
+            }
+            output[i..i + 3].copy_from_slice(&values);
+        }
+    }
+    output
+}
+
+/// Subtract one set of raw pixels from another, channel by channel, leaving the alpha of `photon_image` untouched.
+fn set_difference(photon_image: &mut PhotonImage, a: &[u8], b: &[u8]) {
+    for (i, px) in photon_image.raw_pixels.chunks_mut(4).enumerate() {
+        for channel in 0..3 {
+            px[channel] = a[i * 4 + channel].saturating_sub(b[i * 4 + channel]);
+        }
+    }
+}
+
+/// Erode an image, shrinking bright regions and widening dark ones.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `shape` - The structuring element. Square = 1, Cross = 2, Disk = 3.
+/// * `radius` - The radius of the structuring element, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to erode an image with a disk of radius 2:
+/// use photon::morphology::{erode, StructuringElement};
+/// erode(&mut img, StructuringElement::Disk, 2);
+/// ```
+#[wasm_bindgen]
+pub fn erode(photon_image: &mut PhotonImage, shape: StructuringElement, radius: u32) {
+    photon_image.raw_pixels = rank_filter(photon_image, shape, radius, false);
+}
+
+/// Dilate an image, growing bright regions and shrinking dark ones.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `shape` - The structuring element. Square = 1, Cross = 2, Disk = 3.
+/// * `radius` - The radius of the structuring element, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to dilate an image with a 5x5 square:
+/// use photon::morphology::{dilate, StructuringElement};
+/// dilate(&mut img, StructuringElement::Square, 2);
+/// ```
+#[wasm_bindgen]
+pub fn dilate(photon_image: &mut PhotonImage, shape: StructuringElement, radius: u32) {
+    photon_image.raw_pixels = rank_filter(photon_image, shape, radius, true);
+}
+
+/// Open an image: an erosion followed by a dilation. This removes bright specks smaller than the structuring element.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `shape` - The structuring element. Square = 1, Cross = 2, Disk = 3.
+/// * `radius` - The radius of the structuring element, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to remove salt noise from a scanned page:
+/// use photon::morphology::{open, StructuringElement};
+/// open(&mut img, StructuringElement::Square, 1);
+/// ```
+#[wasm_bindgen]
+pub fn open(photon_image: &mut PhotonImage, shape: StructuringElement, radius: u32) {
+    erode(photon_image, shape, radius);
+    dilate(photon_image, shape, radius);
+}
+
+/// Close an image: a dilation followed by an erosion. This fills dark holes and gaps smaller than the structuring element.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `shape` - The structuring element. Square = 1, Cross = 2, Disk = 3.
+/// * `radius` - The radius of the structuring element, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to join broken strokes in binarized text:
+/// use photon::morphology::{close, StructuringElement};
+/// close(&mut img, StructuringElement::Cross, 1);
+/// ```
+#[wasm_bindgen]
+pub fn close(photon_image: &mut PhotonImage, shape: StructuringElement, radius: u32) {
+    dilate(photon_image, shape, radius);
+    erode(photon_image, shape, radius);
+}
+
+/// Morphological gradient: the difference between the dilation and the erosion of an image, which outlines edges.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `shape` - The structuring element. Square = 1, Cross = 2, Disk = 3.
+/// * `radius` - The radius of the structuring element, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to outline the shapes in an image:
+/// use photon::morphology::{morphological_gradient, StructuringElement};
+/// morphological_gradient(&mut img, StructuringElement::Disk, 1);
+/// ```
+#[wasm_bindgen]
+pub fn morphological_gradient(photon_image: &mut PhotonImage, shape: StructuringElement, radius: u32) {
+    let dilated = rank_filter(photon_image, shape, radius, true);
+    let eroded = rank_filter(photon_image, shape, radius, false);
+    set_difference(photon_image, &dilated, &eroded);
+}
+
+/// Top-hat transform: the difference between an image and its opening, which keeps only bright details smaller than the structuring element.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `shape` - The structuring element. Square = 1, Cross = 2, Disk = 3.
+/// * `radius` - The radius of the structuring element, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to pick out small bright features on an uneven background:
+/// use photon::morphology::{top_hat, StructuringElement};
+/// top_hat(&mut img, StructuringElement::Disk, 7);
+/// ```
+#[wasm_bindgen]
+pub fn top_hat(photon_image: &mut PhotonImage, shape: StructuringElement, radius: u32) {
+    let original = photon_image.raw_pixels.clone();
+    let mut opened = PhotonImage::new(original.clone(), photon_image.width, photon_image.height);
+    open(&mut opened, shape, radius);
+    set_difference(photon_image, &original, &opened.raw_pixels);
+}
+
+/// Black-hat transform: the difference between the closing of an image and the image, which keeps only dark details smaller than the structuring element.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `shape` - The structuring element. Square = 1, Cross = 2, Disk = 3.
+/// * `radius` - The radius of the structuring element, in pixels.
+///
+/// # Example
+///
+/// ```
+/// // For example, to pick out dark text on an unevenly lit page:
+/// use photon::morphology::{black_hat, StructuringElement};
+/// black_hat(&mut img, StructuringElement::Square, 7);
+/// ```
+#[wasm_bindgen]
+pub fn black_hat(photon_image: &mut PhotonImage, shape: StructuringElement, radius: u32) {
+    let original = photon_image.raw_pixels.clone();
+    let mut closed = PhotonImage::new(original.clone(), photon_image.width, photon_image.height);
+    close(&mut closed, shape, radius);
+    set_difference(photon_image, &closed.raw_pixels, &original);
+}
@@ -3,10 +3,12 @@
 extern crate image;
 extern crate rand;
 use image::{DynamicImage, GenericImageView, RgbaImage};
-use palette::{Srgba, LinSrgba, Lab, Blend, Lch, Pixel, Gradient, Srgb};
-use crate::channels::color_sim;
+use palette::{Srgba, Srgb, LinSrgba, Blend, Lch, Pixel, Gradient};
+use crate::colour_spaces::Oklch;
+use crate::channels::{DeltaE, delta_e, falloff_weight, lab_from_rgb, mix_pixels};
 use crate::{PhotonImage, Rgb, helpers, GenericImage};
 use wasm_bindgen::prelude::*;
+use serde::{Serialize, Deserialize};
 
 /// Add a watermark to an image.
 /// 
@@ -30,6 +32,86 @@ pub fn watermark(mut img: &mut PhotonImage, watermark: PhotonImage, x: u32, y: u
     img.raw_pixels = dyn_img.raw_pixels();
 }
 
+/// Blend modes, as found in the layers panel of photo editors.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    Normal = 1,
+    Multiply = 2,
+    Screen = 3,
+    Overlay = 4,
+    Darken = 5,
+    Lighten = 6,
+    Dodge = 7,
+    Burn = 8,
+    HardLight = 9,
+    SoftLight = 10,
+    Difference = 11,
+    Exclusion = 12,
+    Plus = 13,
+    Atop = 14,
+    Xor = 15,
+}
+
+impl BlendMode {
+    /// Get the blend mode with a name such as `multiply` or `soft_light`, following the names used by `blend`.
+    /// `over` and `normal` both name the normal blend mode.
+    pub fn from_name(name: &str) -> Option<BlendMode> {
+        match name {
+            "over" | "normal" => Some(BlendMode::Normal),
+            "multiply" => Some(BlendMode::Multiply),
+            "screen" => Some(BlendMode::Screen),
+            "overlay" => Some(BlendMode::Overlay),
+            "darken" => Some(BlendMode::Darken),
+            "lighten" => Some(BlendMode::Lighten),
+            "dodge" => Some(BlendMode::Dodge),
+            "burn" => Some(BlendMode::Burn),
+            "hard_light" => Some(BlendMode::HardLight),
+            "soft_light" => Some(BlendMode::SoftLight),
+            "difference" => Some(BlendMode::Difference),
+            "exclusion" => Some(BlendMode::Exclusion),
+            "plus" => Some(BlendMode::Plus),
+            "atop" => Some(BlendMode::Atop),
+            "xor" => Some(BlendMode::Xor),
+            _ => None,
+        }
+    }
+}
+
+/// Blend a pixel onto another, taking both pixels' alpha into account.
+///
+/// Unlike `blend`, which keeps its original output for existing callers, this blends the gamma-encoded
+/// values as photo editors and CSS do. It's used to composite the layers of a `document::Document`.
+///
+/// # Arguments
+/// * `base` - The RGBA values of the lower pixel.
+/// * `top` - The RGBA values of the pixel blended onto it.
+/// * `mode` - The blend mode.
+pub fn blend_pixels(base: [u8; 4], top: [u8; 4], mode: BlendMode) -> [u8; 4] {
+    // Blend the gamma-encoded values, as photo editors and CSS do
+    let color: LinSrgba = LinSrgba::from_raw(&base).into_format();
+    let color2: LinSrgba = LinSrgba::from_raw(&top).into_format();
+
+    let blended = match mode {
+        BlendMode::Normal => color2.over(color),
+        BlendMode::Multiply => color2.multiply(color),
+        BlendMode::Screen => color2.screen(color),
+        BlendMode::Overlay => color2.overlay(color),
+        BlendMode::Darken => color2.darken(color),
+        BlendMode::Lighten => color2.lighten(color),
+        BlendMode::Dodge => color2.dodge(color),
+        BlendMode::Burn => color2.burn(color),
+        BlendMode::HardLight => color2.hard_light(color),
+        BlendMode::SoftLight => color2.soft_light(color),
+        BlendMode::Difference => color2.difference(color),
+        BlendMode::Exclusion => color2.exclusion(color),
+        BlendMode::Plus => color2.plus(color),
+        BlendMode::Atop => color2.atop(color),
+        BlendMode::Xor => color2.xor(color),
+    };
+    blended.into_format().into_raw()
+}
+
 /// Blend two images together.
 /// The `blend_mode` (3rd param) determines which blending mode to use; change this for varying effects.
 /// The blend modes available include: `overlay`, `over`, `atop`, `xor`, `multiply`, `burn`, `soft_light`, `hard_light`
@@ -104,43 +186,47 @@ pub fn blend(mut photon_image: &mut PhotonImage, photon_image2: &PhotonImage, bl
 
 /// Change the background of an image (using a green screen/color screen).
 /// 
+/// Pixels whose colour difference from `background_color` lies within `tolerance` are fully replaced,
+/// and pixels within the falloff range beyond it are blended with the new background, which softens the matte's edges.
+/// 
 /// # Arguments
 /// * `img` - A PhotonImage which contains the desired background. Must be the same size as img2.
 /// * `img2` - The image you would like to swap the background of. Must be the same size as img.
 /// * `background_color` - The RGB value of the background, which should be replaced.
+/// * `metric` - The colour difference formula used to compare colours. See `DeltaE`.
+/// * `tolerance` - Pixels within this colour difference of `background_color` are fully replaced. 20.0 is a reasonable default for CIE76.
+/// * `falloff` - Width of the soft edge beyond `tolerance`. 0.0 gives a hard cut-off.
 /// # Example
 ///
 /// ```
 /// // For example, to replace the background of ImageA (which is RGB value 20, 40, 60) with the background of ImageB:
 /// use photon::multiple;
 /// let rgb = Rgb{20, 40, 60};
-/// photon::multiple::replace_background(img_b, img_a, rgb);
+/// photon::multiple::replace_background(img_b, img_a, rgb, DeltaE::Cie76, 20.0, 5.0);
 /// ```
-pub fn replace_background(mut photon_image: &mut PhotonImage, img2: &PhotonImage, background_color: Rgb) {
+pub fn replace_background(photon_image: &mut PhotonImage, img2: &PhotonImage, background_color: Rgb, metric: DeltaE, tolerance: f32, falloff: f32) {
     let mut img = helpers::dyn_image_from_raw(&photon_image);
     let img2 = helpers::dyn_image_from_raw(&img2);
     let (width, height) = img.dimensions();
+
+    // Convert the background colour to the l*a*b colour space
+    let lab = lab_from_rgb(background_color.r, background_color.g, background_color.b);
+
     for x in 0..width {
         for y in 0..height {
             let px = img.get_pixel(x, y);
 
-            // Convert the current pixel's colour to the l*a*b colour space
-            let lab: Lab = Srgb::new(background_color.r as f32 / 255.0, background_color.g as f32 / 255.0, background_color.b as f32 / 255.0).into();
-
-            let r_val: f32 = px.data[0] as f32 / 255.0;
-            let g_val: f32 = px.data[1] as f32 / 255.0;
-            let b_val: f32 = px.data[2] as f32 / 255.0;
+            let px_lab = lab_from_rgb(px.data[0], px.data[1], px.data[2]);
 
-            let px_lab: Lab = Srgb::new(r_val, g_val, b_val).into();
-
-            let sim = color_sim(lab, px_lab);
+            let weight = falloff_weight(delta_e(lab, px_lab, metric), tolerance, falloff);
 
             // Match
-            if sim < 20 {
-                img.put_pixel(x, y, img2.get_pixel(x, y));
-            }
-            else {
-                img.put_pixel(x, y, px);
+            if weight > 0.0 {
+                let background = img2.get_pixel(x, y).data;
+                let mut new_px = mix_pixels(px.data, background, weight);
+                // The new background's alpha is blended in too, so fully replaced pixels take it exactly
+                new_px[3] = (px.data[3] as f32 + (background[3] as f32 - px.data[3] as f32) * weight).round() as u8;
+                img.put_pixel(x, y, image::Rgba(new_px));
             }
         }
     }
@@ -187,6 +273,48 @@ pub fn create_gradient(width: u32, height: u32) -> PhotonImage {
     return PhotonImage { raw_pixels: raw_pixels, width: width, height: height};
 }
 
+/// Create a horizontal gradient between two colours, interpolated in the Oklch colour space.
+/// 
+/// Interpolating in Oklch keeps the lightness and saturation of the gradient perceptually even,
+/// and hues travel the shortest way around the colour wheel, without the dull or purple midpoints
+/// of gradients blended in sRGB or CIE LCh.
+/// 
+/// # Arguments
+/// * `width` - The width of the gradient image.
+/// * `height` - The height of the gradient image.
+/// * `start` - The colour at the left edge of the gradient.
+/// * `end` - The colour at the right edge of the gradient.
+/// # Example
+///
+/// ```
+/// // For example, to create a 400x100 gradient from orange to blue:
+/// use photon::multiple::create_gradient_oklch;
+/// let gradient = create_gradient_oklch(400, 100, Rgb::new(255, 140, 0), Rgb::new(20, 60, 255));
+/// ```
+#[wasm_bindgen]
+pub fn create_gradient_oklch(width: u32, height: u32, start: Rgb, end: Rgb) -> PhotonImage {
+    let to_oklch = |colour: &Rgb| {
+        let rgb = Srgb::new(colour.r as f32 / 255.0, colour.g as f32 / 255.0, colour.b as f32 / 255.0);
+        Oklch::from(rgb.into_linear())
+    };
+    let gradient = Gradient::new(vec![to_oklch(&start), to_oklch(&end)]);
+
+    // Sample so that the first and last columns land exactly on the start and end colours
+    let last_column = width.saturating_sub(1).max(1) as f32;
+    let columns: Vec<[u8; 3]> = (0..width)
+        .map(|x| gradient.get(x as f32 / last_column))
+        .map(|colour| Srgb::from_linear(colour.into()).into_format().into_raw())
+        .collect();
+
+    let mut raw_pixels = Vec::with_capacity((width * height * 4) as usize);
+    for _ in 0..height {
+        for colour in columns.iter() {
+            raw_pixels.extend_from_slice(&[colour[0], colour[1], colour[2], 255]);
+        }
+    }
+    PhotonImage { raw_pixels, width, height }
+}
+
 /// Apply a gradient to an image.
 #[wasm_bindgen]
 pub fn apply_gradient(mut image: &mut PhotonImage) {
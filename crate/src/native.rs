@@ -6,6 +6,9 @@ extern crate rand;
 use image::{GenericImageView, ImageBuffer};
 // use wasm_bindgen::prelude::*;
 use crate::{PhotonImage};
+use crate::tiles::{process_stream, PngRowSink, PngRowSource, RowSource};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
 
 /// Open an image at a given path from the filesystem.
 /// A PhotonImage is returned.
@@ -57,4 +60,36 @@ pub fn save_image(img: PhotonImage, img_path: &str) {
     let dynimage = image::ImageRgba8(img_buffer);
     
     dynimage.save(img_path).unwrap();
-}
\ No newline at end of file
+}
+
+/// Apply an operation to a PNG file, streaming it from disk to disk a strip of rows at a time,
+/// so the whole image is never held in memory. See `tiles::process_stream` for which operations can be streamed.
+/// # Arguments
+/// * `input_path` - Path to the PNG you wish to edit. It mustn't be interlaced.
+/// * `output_path` - Path for the outputted PNG.
+/// * `strip_height` - The number of rows processed at a time.
+/// * `halo` - The number of neighbouring rows each strip needs, such as 1 for a 3x3 convolution.
+/// * `operation` - The operation to apply.
+///
+/// Returns an error if either file can't be opened, or the input isn't a non-interlaced PNG.
+///
+/// # Example
+/// ```
+/// // For example:
+/// use photon::native::process_png_file;
+/// use photon::conv::sharpen;
+///
+/// // Sharpen a huge scan, 256 rows at a time.
+/// process_png_file("images/scan.png", "images/sharpened.png", 256, 1, sharpen)?;
+/// ```
+pub fn process_png_file<F>(input_path: &str, output_path: &str, strip_height: u32, halo: u32, operation: F) -> io::Result<()>
+    where F: FnMut(&mut PhotonImage) {
+    let input = BufReader::new(File::open(input_path)?);
+    let mut source = PngRowSource::new(input)?;
+    let (width, height) = source.dimensions();
+
+    let output = BufWriter::new(File::create(output_path)?);
+    let mut sink = PngRowSink::new(output, width, height)?;
+    process_stream(&mut source, &mut sink, strip_height, halo, operation)?;
+    sink.finish()
+}
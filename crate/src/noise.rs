@@ -5,15 +5,60 @@ extern crate rand;
 use image::{GenericImage, GenericImageView};
 use rand::Rng;
 use image::{Pixel};
-// use wasm_bindgen::prelude::*;
+use wasm_bindgen::prelude::*;
 use crate::{PhotonImage};
 use crate::{helpers};
 
+/// A small, seedable pseudo-random number generator (SplitMix64).
+///
+/// Unlike `rand::thread_rng`, it needs no source of entropy from the operating system, so it works
+/// in WebAssembly, and the same seed always gives the same sequence on every platform.
+pub(crate) struct Prng {
+    state: u64,
+}
+
+impl Prng {
+    pub(crate) fn new(seed: u32) -> Prng {
+        Prng { state: seed as u64 ^ 0x5851_F42D_4C95_7F2D }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed float from 0 (inclusive) to 1 (exclusive).
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A normally distributed float with a mean of 0 and a standard deviation of 1, using the Box-Muller transform.
+    pub(crate) fn next_gaussian(&mut self) -> f32 {
+        let u1 = 1.0 - self.next_f32();
+        let u2 = self.next_f32();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+    }
+}
+
+/// Add a per-pixel offset to an image, either the same offset on every channel (monochrome) or a separate one per channel.
+fn add_offsets<F>(photon_image: &mut PhotonImage, monochrome: bool, mut offset: F) where F: FnMut() -> f32 {
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        let shared = if monochrome { offset() } else { 0.0 };
+        for channel in px.iter_mut().take(3) {
+            let delta = if monochrome { shared } else { offset() };
+            *channel = num::clamp((*channel as f32 + delta).round(), 0.0, 255.0) as u8;
+        }
+    }
+}
+
 /// Add randomized noise to an image. 
 /// This function adds a Gaussian Noise Sample to each pixel through incrementing each channel by a randomized offset.
 /// This randomized offset is generated by creating a randomized thread pool.
-/// **[WASM SUPPORT NOT AVAILABLE]**: Randomized thread pools cannot be created with WASM using the code used currently, but 
-/// a workaround is oncoming. 
+/// **[WASM SUPPORT NOT AVAILABLE]**: Randomized thread pools cannot be created with WASM.
+/// Use `add_gaussian_noise` or `add_uniform_noise`, which take a seed and work in WASM.
 /// # Arguments
 /// * `img` - A PhotonImage.
 /// 
@@ -42,8 +87,8 @@ pub fn add_noise_rand(mut photon_image: PhotonImage) -> PhotonImage {
 
 /// Add pink-tinted noise to an image. 
 /// 
-/// **[WASM SUPPORT NOT AVAILABLE]**: Randomized thread pools cannot be created using the code used currently, but 
-/// support is oncoming. 
+/// **[WASM SUPPORT NOT AVAILABLE]**: Randomized thread pools cannot be created with WASM.
+/// Use `pink_noise_seeded`, which takes a seed and works in WASM.
 /// # Arguments
 /// * `name` - A PhotonImage that contains a view into the image.
 /// 
@@ -54,27 +99,153 @@ pub fn add_noise_rand(mut photon_image: PhotonImage) -> PhotonImage {
 /// use photon::noise;
 /// photon::noise::pink_noise(img);
 /// ```
-pub fn pink_noise(mut photon_image: &mut PhotonImage) {
-    let mut img = helpers::dyn_image_from_raw(&photon_image);
-    let (width, height) = img.dimensions();
-    let mut rng = rand::thread_rng();
+pub fn pink_noise(photon_image: &mut PhotonImage) {
+    let seed: u32 = rand::thread_rng().gen();
+    pink_noise_seeded(photon_image, seed);
+}
 
-    for x in 0..width {
-        for y in 0..height {
-            let ran1: f64 = rng.gen(); // generates a float between 0 and 1
-            let ran2: f64 = rng.gen();
-            let ran3: f64 = rng.gen();
-
-            let ran_color1: f64 = 0.6 + ran1 * 0.6;
-            let ran_color2: f64 = 0.6 + ran2 * 0.1;
-            let ran_color3: f64 = 0.6 + ran3 * 0.4;
-
-            let mut px = img.get_pixel(x, y);
-            px.data[0] = (px.data[0] as f64 * 0.99 * ran_color1) as u8;
-            px.data[1] = (px.data[1] as f64 * 0.99 * ran_color2) as u8;
-            px.data[2] = (px.data[2] as f64 * 0.99 * ran_color3) as u8;
-            img.put_pixel(x, y, px);
+/// Add pink-tinted noise to an image, using a seed so that the result can be reproduced.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `seed` - The seed for the random number generator. The same seed always gives the same noise.
+///
+/// # Example
+///
+/// ```
+/// // For example, to add pink-tinted noise to an image:
+/// use photon::noise;
+/// photon::noise::pink_noise_seeded(&mut img, 42);
+/// ```
+#[wasm_bindgen]
+pub fn pink_noise_seeded(photon_image: &mut PhotonImage, seed: u32) {
+    let mut rng = Prng::new(seed);
+
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        let ran_color1 = 0.6 + rng.next_f32() as f64 * 0.6;
+        let ran_color2 = 0.6 + rng.next_f32() as f64 * 0.1;
+        let ran_color3 = 0.6 + rng.next_f32() as f64 * 0.4;
+
+        px[0] = (px[0] as f64 * 0.99 * ran_color1) as u8;
+        px[1] = (px[1] as f64 * 0.99 * ran_color2) as u8;
+        px[2] = (px[2] as f64 * 0.99 * ran_color3) as u8;
+    }
+}
+
+/// Add Gaussian noise to an image.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `sigma` - The standard deviation of the noise, on the 0 to 255 channel scale.
+/// * `monochrome` - Whether to add the same noise to every channel of a pixel, rather than separate noise per channel.
+/// * `seed` - The seed for the random number generator. The same seed always gives the same noise.
+///
+/// # Example
+///
+/// ```
+/// // For example, to add subtle colour noise to an image:
+/// use photon::noise;
+/// photon::noise::add_gaussian_noise(&mut img, 8.0, false, 42);
+/// ```
+#[wasm_bindgen]
+pub fn add_gaussian_noise(photon_image: &mut PhotonImage, sigma: f32, monochrome: bool, seed: u32) {
+    let mut rng = Prng::new(seed);
+    add_offsets(photon_image, monochrome, || rng.next_gaussian() * sigma);
+}
+
+/// Add uniformly distributed noise to an image.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `amount` - The largest offset added to or subtracted from a channel, on the 0 to 255 channel scale.
+/// * `monochrome` - Whether to add the same noise to every channel of a pixel, rather than separate noise per channel.
+/// * `seed` - The seed for the random number generator. The same seed always gives the same noise.
+///
+/// # Example
+///
+/// ```
+/// // For example, to add monochrome noise to an image:
+/// use photon::noise;
+/// photon::noise::add_uniform_noise(&mut img, 20.0, true, 42);
+/// ```
+#[wasm_bindgen]
+pub fn add_uniform_noise(photon_image: &mut PhotonImage, amount: f32, monochrome: bool, seed: u32) {
+    let mut rng = Prng::new(seed);
+    add_offsets(photon_image, monochrome, || (rng.next_f32() * 2.0 - 1.0) * amount);
+}
+
+/// Add salt-and-pepper noise to an image, setting randomly chosen pixels to pure white or pure black.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `density` - The proportion of pixels to replace, from 0 to 1.
+/// * `seed` - The seed for the random number generator. The same seed always gives the same noise.
+///
+/// # Example
+///
+/// ```
+/// // For example, to replace 5% of pixels with salt-and-pepper noise:
+/// use photon::noise;
+/// photon::noise::add_salt_and_pepper_noise(&mut img, 0.05, 42);
+/// ```
+#[wasm_bindgen]
+pub fn add_salt_and_pepper_noise(photon_image: &mut PhotonImage, density: f32, seed: u32) {
+    let mut rng = Prng::new(seed);
+    for px in photon_image.raw_pixels.chunks_mut(4) {
+        if rng.next_f32() < density {
+            let value = if rng.next_f32() < 0.5 { 0 } else { 255 };
+            px[0] = value;
+            px[1] = value;
+            px[2] = value;
         }
     }
-    photon_image.raw_pixels = img.raw_pixels();
-}
\ No newline at end of file
+}
+
+/// Add film grain to an image.
+///
+/// The grain is monochrome, clumped to the given size, and strongest in the midtones, as with real film,
+/// so that highlights and shadows stay clean.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `amount` - The strength of the grain, as the standard deviation in the midtones on the 0 to 255 channel scale.
+/// * `grain_size` - The size of the grain clumps, in pixels. 1 gives per-pixel grain.
+/// * `seed` - The seed for the random number generator. The same seed always gives the same grain.
+///
+/// # Example
+///
+/// ```
+/// // For example, to add fine grain to an image:
+/// use photon::noise;
+/// photon::noise::film_grain(&mut img, 12.0, 1.5, 42);
+/// ```
+#[wasm_bindgen]
+pub fn film_grain(photon_image: &mut PhotonImage, amount: f32, grain_size: f32, seed: u32) {
+    let mut rng = Prng::new(seed);
+    let grain_size = grain_size.max(1.0);
+    let width = photon_image.width as usize;
+
+    // Generate grain on a coarse grid and interpolate between its points, which gives smooth clumps
+    let grid_width = (photon_image.width as f32 / grain_size).ceil() as usize + 2;
+    let grid_height = (photon_image.height as f32 / grain_size).ceil() as usize + 2;
+    let grid: Vec<f32> = (0..grid_width * grid_height).map(|_| rng.next_gaussian()).collect();
+    // Bilinear interpolation averages neighbouring values, which lowers the variance; this restores it
+    let correction = if grain_size > 1.0 { 1.5 } else { 1.0 };
+
+    for (i, px) in photon_image.raw_pixels.chunks_mut(4).enumerate() {
+        let gx = (i % width) as f32 / grain_size;
+        let gy = (i / width) as f32 / grain_size;
+        let (x0, y0) = (gx.floor() as usize, gy.floor() as usize);
+        let (fx, fy) = (gx.fract(), gy.fract());
+        let top = grid[y0 * grid_width + x0] * (1.0 - fx) + grid[y0 * grid_width + x0 + 1] * fx;
+        let bottom = grid[(y0 + 1) * grid_width + x0] * (1.0 - fx) + grid[(y0 + 1) * grid_width + x0 + 1] * fx;
+        let grain = (top * (1.0 - fy) + bottom * fy) * correction;
+
+        let luma = (0.2126 * px[0] as f32 + 0.7152 * px[1] as f32 + 0.0722 * px[2] as f32) / 255.0;
+        let midtones = 4.0 * luma * (1.0 - luma);
+        let delta = grain * amount * midtones;
+        for channel in px.iter_mut().take(3) {
+            *channel = num::clamp((*channel as f32 + delta).round(), 0.0, 255.0) as u8;
+        }
+    }
+}
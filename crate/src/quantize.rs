@@ -0,0 +1,270 @@
+//! Colour quantization and palette extraction.
+
+extern crate image;
+use std::collections::HashMap;
+use palette::{Lab, LinSrgb, Pixel, Srgb};
+use serde::{Serialize, Deserialize};
+use wasm_bindgen::prelude::*;
+use crate::channels::lab_from_rgb;
+use crate::dither::{dither_to_palette, map_to_palette, DitherAlgorithm};
+use crate::{PhotonImage, Rgb};
+
+/// A colour taken from an image, along with the proportion of the image's pixels it represents.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PaletteColour {
+    colour: Rgb,
+    proportion: f32,
+}
+
+#[wasm_bindgen]
+impl PaletteColour {
+    /// Get the colour.
+    pub fn get_colour(&self) -> Rgb {
+        self.colour
+    }
+
+    /// Get the proportion of the image's pixels, from 0 to 1, that are closest to this colour.
+    pub fn get_proportion(&self) -> f32 {
+        self.proportion
+    }
+}
+
+/// A colour sample used during clustering: a position in the l*a*b colour space and the number of pixels it stands for.
+#[derive(Clone, Copy)]
+struct Sample {
+    lab: [f32; 3],
+    weight: f32,
+}
+
+/// Build a weighted list of colour samples from an image.
+///
+/// Pixels are first grouped into a 32x32x32 RGB histogram so that clustering runs over at most
+/// 32768 samples, whatever the size of the image. Fully transparent pixels are ignored.
+fn colour_samples(photon_image: &PhotonImage) -> Vec<Sample> {
+    let mut bins: HashMap<u16, (u32, u32, u32, u32)> = HashMap::new();
+
+    for px in photon_image.raw_pixels.chunks(4) {
+        if px[3] == 0 {
+            continue;
+        }
+        let key = ((px[0] as u16 >> 3) << 10) | ((px[1] as u16 >> 3) << 5) | (px[2] as u16 >> 3);
+        let bin = bins.entry(key).or_insert((0, 0, 0, 0));
+        bin.0 += px[0] as u32;
+        bin.1 += px[1] as u32;
+        bin.2 += px[2] as u32;
+        bin.3 += 1;
+    }
+
+    let mut keys: Vec<u16> = bins.keys().cloned().collect();
+    // Sort so that the clustering is deterministic regardless of hash ordering
+    keys.sort();
+
+    keys.iter()
+        .map(|key| {
+            let (r, g, b, count) = bins[key];
+            let lab = lab_from_rgb((r / count) as u8, (g / count) as u8, (b / count) as u8);
+            Sample { lab: [lab.l, lab.a, lab.b], weight: count as f32 }
+        })
+        .collect()
+}
+
+fn distance_sq(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+fn weighted_mean(samples: &[Sample]) -> [f32; 3] {
+    let mut sum = [0.0; 3];
+    let mut total = 0.0;
+    for sample in samples {
+        for (acc, value) in sum.iter_mut().zip(sample.lab.iter()) {
+            *acc += value * sample.weight;
+        }
+        total += sample.weight;
+    }
+    [sum[0] / total, sum[1] / total, sum[2] / total]
+}
+
+/// Pick up to `n` initial cluster centres by median cut.
+///
+/// The box with the widest spread along any axis is repeatedly split at the weighted median of that axis,
+/// until there are `n` boxes or no box can be split further.
+fn median_cut(samples: &[Sample], n: usize) -> Vec<[f32; 3]> {
+    let mut boxes: Vec<Vec<Sample>> = vec![samples.to_vec()];
+
+    while boxes.len() < n {
+        // Find the box and axis with the largest range
+        let mut best: Option<(usize, usize, f32)> = None;
+        for (index, samples) in boxes.iter().enumerate() {
+            if samples.len() < 2 {
+                continue;
+            }
+            for axis in 0..3 {
+                let min = samples.iter().map(|s| s.lab[axis]).fold(f32::MAX, f32::min);
+                let max = samples.iter().map(|s| s.lab[axis]).fold(f32::MIN, f32::max);
+                let range = max - min;
+                let widest = match best {
+                    Some((_, _, best_range)) => range > best_range,
+                    None => true,
+                };
+                if widest {
+                    best = Some((index, axis, range));
+                }
+            }
+        }
+
+        let (index, axis) = match best {
+            Some((index, axis, range)) if range > 0.0 => (index, axis),
+            _ => break,
+        };
+
+        let mut samples = boxes.swap_remove(index);
+        samples.sort_by(|a, b| a.lab[axis].partial_cmp(&b.lab[axis]).unwrap());
+
+        let half = samples.iter().map(|s| s.weight).sum::<f32>() / 2.0;
+        let mut running = 0.0;
+        let mut split = 1;
+        for (i, sample) in samples.iter().enumerate() {
+            running += sample.weight;
+            if running >= half {
+                split = i + 1;
+                break;
+            }
+        }
+        // Both halves must keep at least one sample
+        let split = split.max(1).min(samples.len() - 1);
+
+        let upper = samples.split_off(split);
+        boxes.push(samples);
+        boxes.push(upper);
+    }
+
+    boxes.iter().map(|samples| weighted_mean(samples)).collect()
+}
+
+/// Refine cluster centres with weighted k-means (Lloyd's algorithm) in the l*a*b colour space.
+/// Returns the final centres along with the total weight assigned to each.
+fn k_means(samples: &[Sample], mut centres: Vec<[f32; 3]>) -> (Vec<[f32; 3]>, Vec<f32>) {
+    let max_iterations = 10;
+    let mut weights = vec![0.0; centres.len()];
+
+    for _ in 0..max_iterations {
+        let mut sums = vec![[0.0f32; 3]; centres.len()];
+        weights = vec![0.0; centres.len()];
+
+        for sample in samples {
+            let nearest = nearest_index(&centres, &sample.lab);
+            for (acc, value) in sums[nearest].iter_mut().zip(sample.lab.iter()) {
+                *acc += value * sample.weight;
+            }
+            weights[nearest] += sample.weight;
+        }
+
+        let mut moved = 0.0f32;
+        for (i, centre) in centres.iter_mut().enumerate() {
+            if weights[i] > 0.0 {
+                let new_centre = [sums[i][0] / weights[i], sums[i][1] / weights[i], sums[i][2] / weights[i]];
+                moved = moved.max(distance_sq(centre, &new_centre));
+                *centre = new_centre;
+            }
+        }
+
+        // Stop once no centre moves by more than a quarter of a just-noticeable difference
+        if moved < 0.0625 {
+            break;
+        }
+    }
+    (centres, weights)
+}
+
+fn nearest_index(centres: &[[f32; 3]], lab: &[f32; 3]) -> usize {
+    let mut nearest = 0;
+    let mut nearest_distance = f32::MAX;
+    for (i, centre) in centres.iter().enumerate() {
+        let distance = distance_sq(centre, lab);
+        if distance < nearest_distance {
+            nearest = i;
+            nearest_distance = distance;
+        }
+    }
+    nearest
+}
+
+fn rgb_from_lab(lab: &[f32; 3]) -> Rgb {
+    let linear: LinSrgb = Lab::new(lab[0], lab[1], lab[2]).into();
+    let raw: [u8; 3] = Srgb::from_linear(linear).into_format().into_raw();
+    Rgb::new(raw[0], raw[1], raw[2])
+}
+
+/// Extract the dominant colours of an image.
+///
+/// Colours are clustered in the l*a*b colour space using median cut followed by k-means refinement,
+/// so the palette reflects perceptual differences rather than raw RGB distances.
+/// Fully transparent pixels are ignored.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `n` - The maximum number of colours to extract. Fewer are returned if the image has fewer distinct colours.
+///
+/// Returns the colours sorted from most to least common, each with the proportion of pixels it represents.
+///
+/// # Example
+///
+/// ```
+/// // For example, to get the five most dominant colours of an image:
+/// use photon::quantize::extract_palette;
+/// let palette = extract_palette(&img, 5);
+/// let main_colour = palette[0].get_colour();
+/// ```
+#[wasm_bindgen]
+pub fn extract_palette(photon_image: &PhotonImage, n: usize) -> Vec<PaletteColour> {
+    let samples = colour_samples(photon_image);
+    if samples.is_empty() || n == 0 {
+        return vec![];
+    }
+
+    let (centres, weights) = k_means(&samples, median_cut(&samples, n));
+    let total: f32 = weights.iter().sum();
+
+    let mut palette: Vec<PaletteColour> = centres.iter()
+        .zip(weights.iter())
+        .filter(|(_, weight)| **weight > 0.0)
+        .map(|(centre, weight)| PaletteColour { colour: rgb_from_lab(centre), proportion: weight / total })
+        .collect();
+    palette.sort_by(|a, b| b.proportion.partial_cmp(&a.proportion).unwrap());
+    palette
+}
+
+/// Reduce an image to a limited number of colours.
+///
+/// The palette is chosen with `extract_palette`, and each pixel is replaced by its perceptually
+/// closest palette colour. This is useful for GIF or PNG8 exports, or for a posterized look.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `n_colours` - The number of colours to reduce the image to.
+/// * `dither` - Whether to apply Floyd-Steinberg error diffusion, which hides banding by scattering
+/// the quantization error to neighbouring pixels. For other dithering algorithms, pass the palette
+/// from `extract_palette` to `dither::dither_to_palette`.
+///
+/// # Example
+///
+/// ```
+/// // For example, to reduce an image to 16 colours with dithering:
+/// use photon::quantize::quantize;
+/// quantize(&mut img, 16, true);
+/// ```
+#[wasm_bindgen]
+pub fn quantize(photon_image: &mut PhotonImage, n_colours: usize, dither: bool) {
+    let palette: Vec<Rgb> = extract_palette(photon_image, n_colours).iter().map(|c| c.colour).collect();
+    if palette.is_empty() {
+        return;
+    }
+
+    if dither {
+        dither_to_palette(photon_image, DitherAlgorithm::FloydSteinberg, palette);
+    }
+    else {
+        map_to_palette(photon_image, &palette);
+    }
+}
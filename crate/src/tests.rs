@@ -2,7 +2,28 @@
 mod test {
     use super::*;
     use crate::channels::*;
+    use crate::colour_spaces::*;
+    use crate::multiple::*;
+    use crate::quantize::*;
+    use crate::dither::*;
+    use crate::morphology::*;
+    use crate::denoise::*;
+    use crate::textures::*;
+    use crate::distort::*;
+    use crate::lut::*;
+    use crate::mask::*;
+    use crate::document::{Document, DocumentError, Layer, LayerContent};
+    use crate::tiles::*;
+    use png::HasParameters;
+    use crate::noise::{add_gaussian_noise, add_uniform_noise, add_salt_and_pepper_noise, film_grain, pink_noise_seeded};
+    use crate::conv::{noise_reduction, unsharp_mask, high_pass, clarity, gaussian_blur, sharpen};
+    use crate::monochrome::{otsu_threshold, adaptive_threshold, sauvola_threshold, niblack_threshold, AdaptiveMethod};
+    use crate::effects::{halftone, halftone_screen, halftone_cmyk, HalftoneShape, vignette, vignette_with_centre};
+    use crate::effects::{solarize, posterize, invert_channels, brightness, inc_brightness, tint};
+    use crate::filters::{neue, lix, ryo, filter, clarendon, FilterError, FilterPipeline, FilterRegistry};
+    use crate::Rgb;
     use crate::{PhotonImage};
+    use palette::{Lab, LinSrgb, Mix};
     #[test]
     fn test_alter_red_channel() {
         let width = 4;
@@ -86,4 +107,1115 @@ mod test {
         swap_channels(&mut photon_image, 1, 0);
         assert_eq!(photon_image.raw_pixels, correct_pix);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_delta_e_cie76() {
+        let lab1 = Lab::new(50.0, 0.0, 0.0);
+        let lab2 = Lab::new(50.0, 3.0, 4.0);
+        assert!((delta_e(lab1, lab2, DeltaE::Cie76) - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_delta_e_cie94_lightness_only() {
+        // With no chroma, CIE94 reduces to the lightness difference
+        let lab1 = Lab::new(50.0, 0.0, 0.0);
+        let lab2 = Lab::new(60.0, 0.0, 0.0);
+        assert!((delta_e(lab1, lab2, DeltaE::Cie94) - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_delta_e_ciede2000() {
+        // Reference pairs from Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference Formula" (2005)
+        let pairs = vec![
+            ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+            ((50.0, 0.0, 0.0), (50.0, -1.0, 2.0), 2.3669),
+            ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+            ((60.2574, -34.0099, 36.2677), (60.4626, -34.1751, 39.4387), 1.2644),
+            ((2.0776, 0.0795, -1.1350), (0.9033, -0.0636, -0.5514), 0.9082),
+        ];
+        for ((l1, a1, b1), (l2, a2, b2), expected) in pairs {
+            let diff = delta_e(Lab::new(l1, a1, b1), Lab::new(l2, a2, b2), DeltaE::Ciede2000);
+            assert!((diff - expected).abs() < 1e-3, "expected {}, got {}", expected, diff);
+        }
+    }
+
+    #[test]
+    fn test_falloff_weight() {
+        assert_eq!(falloff_weight(5.0, 10.0, 4.0), 1.0);
+        assert_eq!(falloff_weight(14.0, 10.0, 4.0), 0.0);
+        assert_eq!(falloff_weight(12.0, 10.0, 4.0), 0.5);
+        assert_eq!(falloff_weight(10.5, 10.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_hsl_panel_targets_band() {
+        // A pure red pixel followed by a pure blue pixel
+        let raw_pix = vec![255, 0, 0, 255, 0, 0, 255, 255];
+        let mut photon_image = PhotonImage::new(raw_pix, 2, 1);
+
+        let mut panel = HslPanel::new();
+        panel.set_saturation(ColourBand::Reds, -1.0);
+        hsl_panel(&mut photon_image, &panel);
+
+        // The red pixel loses all of its saturation, and the blue pixel is left untouched
+        let red = &photon_image.raw_pixels[0..3];
+        let spread = red.iter().max().unwrap() - red.iter().min().unwrap();
+        assert!(spread <= 1, "expected a grey pixel, got {:?}", red);
+        assert_eq!(&photon_image.raw_pixels[4..8], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_oklab_white_and_round_trip() {
+        let white = Oklab::from(LinSrgb::new(1.0, 1.0, 1.0));
+        assert!((white.l - 1.0).abs() < 1e-3);
+        assert!(white.a.abs() < 1e-3 && white.b.abs() < 1e-3);
+
+        let rgb = LinSrgb::new(0.2, 0.5, 0.8);
+        let round_trip = LinSrgb::from(Oklab::from(rgb));
+        assert!((round_trip.red - rgb.red).abs() < 1e-3);
+        assert!((round_trip.green - rgb.green).abs() < 1e-3);
+        assert!((round_trip.blue - rgb.blue).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_adjust_matches_mode_strings() {
+        let raw_pix = vec![134,122,131,255,131,131,139,255,135,134,137,255,138,134,130,255];
+
+        let mut typed = PhotonImage::new(raw_pix.clone(), 2, 2);
+        adjust(&mut typed, ColourSpace::Hsl, ColourAdjustment::ShiftHue, 90.0);
+
+        let mut named = PhotonImage::new(raw_pix, 2, 2);
+        hsl(&mut named, "shift_hue", 0.25).unwrap();
+
+        assert_eq!(typed.raw_pixels, named.raw_pixels);
+
+        // The hue_rotate wrappers take a fraction of a turn, like the mode strings
+        let mut rotated = PhotonImage::new(vec![134,122,131,255,131,131,139,255,135,134,137,255,138,134,130,255], 2, 2);
+        hue_rotate_hsl(&mut rotated, 0.25);
+        assert_eq!(rotated.raw_pixels, typed.raw_pixels);
+    }
+
+    #[test]
+    fn test_unknown_mode_returns_error() {
+        let mut photon_image = PhotonImage::new(vec![134, 122, 131, 255], 1, 1);
+        assert_eq!(lch(&mut photon_image, "saturation", 0.1), Err(ColourSpaceError::UnknownMode("saturation".to_string())));
+        assert_eq!(photon_image.raw_pixels, vec![134, 122, 131, 255]);
+    }
+
+    #[test]
+    fn test_create_gradient_oklch_endpoints() {
+        let gradient = create_gradient_oklch(16, 2, Rgb::new(255, 140, 0), Rgb::new(20, 60, 255));
+        assert_eq!(gradient.raw_pixels.len(), 16 * 2 * 4);
+
+        let first = &gradient.raw_pixels[0..3];
+        let last = &gradient.raw_pixels[15 * 4..15 * 4 + 3];
+        for (actual, expected) in first.iter().zip([255u8, 140, 0].iter()) {
+            assert!((*actual as i16 - *expected as i16).abs() <= 1);
+        }
+        for (actual, expected) in last.iter().zip([20u8, 60, 255].iter()) {
+            assert!((*actual as i16 - *expected as i16).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn test_oklch_mix_takes_shortest_hue_path() {
+        let start = Oklch::new(0.6, 0.1, 350.0);
+        let end = Oklch::new(0.6, 0.1, 10.0);
+        let mid = start.mix(&end, 0.5);
+        assert!(mid.hue.to_degrees().abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_extract_palette_proportions() {
+        // Three quarters red, one quarter blue
+        let mut pixels = vec![];
+        for _ in 0..3 {
+            pixels.extend_from_slice(&[255, 0, 0, 255]);
+        }
+        pixels.extend_from_slice(&[0, 0, 255, 255]);
+        let photon_image = PhotonImage::new(pixels, 2, 2);
+
+        let palette = extract_palette(&photon_image, 4);
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette[0].get_colour(), Rgb::new(255, 0, 0));
+        assert!((palette[0].get_proportion() - 0.75).abs() < 1e-6);
+        assert_eq!(palette[1].get_colour(), Rgb::new(0, 0, 255));
+    }
+
+    #[test]
+    fn test_quantize_limits_colours() {
+        let width = 32;
+        let mut pixels = vec![];
+        for x in 0..width {
+            let v = (x * 8) as u8;
+            pixels.extend_from_slice(&[v, 255 - v, 128, 255]);
+        }
+        for dither in [false, true].iter() {
+            let mut photon_image = PhotonImage::new(pixels.clone(), width, 1);
+            quantize(&mut photon_image, 4, *dither);
+
+            let mut colours: Vec<&[u8]> = photon_image.raw_pixels.chunks(4).collect();
+            colours.sort();
+            colours.dedup();
+            assert!(colours.len() <= 4);
+            assert!(photon_image.raw_pixels.chunks(4).all(|px| px[3] == 255));
+        }
+    }
+
+    #[test]
+    fn test_dither_black_and_white() {
+        let width = 16;
+        let mut pixels = vec![];
+        for x in 0..width * 4 {
+            let v = (x * 4) as u8;
+            pixels.extend_from_slice(&[v, v, v, 255]);
+        }
+        let algorithms = [
+            DitherAlgorithm::FloydSteinberg, DitherAlgorithm::Atkinson, DitherAlgorithm::JarvisJudiceNinke,
+            DitherAlgorithm::Sierra, DitherAlgorithm::Bayer2, DitherAlgorithm::Bayer4, DitherAlgorithm::Bayer8,
+        ];
+        for algorithm in algorithms.iter() {
+            let mut photon_image = PhotonImage::new(pixels.clone(), width, 4);
+            dither(&mut photon_image, *algorithm);
+            assert!(photon_image.raw_pixels.chunks(4).all(|px| (px[0] == 0 || px[0] == 255) && px[0] == px[1] && px[1] == px[2]));
+
+            // A mid-grey gradient dithers to roughly half white pixels
+            let white = photon_image.raw_pixels.chunks(4).filter(|px| px[0] == 255).count() as f32;
+            assert!((white / (width * 4) as f32 - 0.5).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_dither_to_palette_only_uses_palette() {
+        let mut photon_image = PhotonImage::new(vec![200, 30, 30, 255, 30, 200, 30, 128, 90, 90, 200, 255, 250, 250, 250, 255], 2, 2);
+        let palette = vec![Rgb::new(255, 0, 0), Rgb::new(0, 255, 0), Rgb::new(0, 0, 0)];
+        dither_to_palette(&mut photon_image, DitherAlgorithm::Bayer2, palette.clone());
+
+        for px in photon_image.raw_pixels.chunks(4) {
+            assert!(palette.contains(&Rgb::new(px[0], px[1], px[2])));
+        }
+        assert_eq!(photon_image.raw_pixels[7], 128);
+    }
+
+    #[test]
+    fn test_halftone_odd_dimensions() {
+        let mut photon_image = PhotonImage::new(vec![128; 3 * 3 * 4], 3, 3);
+        halftone(&mut photon_image);
+        assert!(photon_image.raw_pixels.chunks(4).all(|px| px[0] == 0 || px[0] == 255));
+    }
+
+    #[test]
+    fn test_halftone_screen_preserves_tone() {
+        let shapes = [HalftoneShape::Round, HalftoneShape::Line, HalftoneShape::Square];
+        for shape in shapes.iter() {
+            // A flat mid-grey should come out roughly half covered in ink
+            let mut photon_image = PhotonImage::new(vec![128; 64 * 64 * 4], 64, 64);
+            halftone_screen(&mut photon_image, 8.0, 45.0, *shape);
+            let mean = photon_image.raw_pixels.chunks(4).map(|px| px[0] as f32).sum::<f32>() / (64.0 * 64.0);
+            assert!((mean - 128.0).abs() < 13.0);
+            assert!(photon_image.raw_pixels.chunks(4).all(|px| px[3] == 128));
+
+            let mut white = PhotonImage::new(vec![255; 16 * 16 * 4], 16, 16);
+            halftone_screen(&mut white, 4.0, 15.0, *shape);
+            assert!(white.raw_pixels.iter().all(|v| *v == 255));
+        }
+    }
+
+    #[test]
+    fn test_halftone_cmyk_solid_colours() {
+        let mut photon_image = PhotonImage::new([255, 0, 0, 255].repeat(16 * 16), 16, 16);
+        halftone_cmyk(&mut photon_image, 6.0, HalftoneShape::Round);
+        assert!(photon_image.raw_pixels.chunks(4).all(|px| px == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_otsu_threshold_bimodal() {
+        let mut pixels = [40, 40, 40, 255].repeat(6);
+        pixels.extend([210, 210, 210, 255].repeat(10));
+        let mut photon_image = PhotonImage::new(pixels, 4, 4);
+
+        let threshold = otsu_threshold(&mut photon_image);
+        assert!(threshold > 40 && threshold <= 210);
+        assert_eq!(&photon_image.raw_pixels[0..4], &[0, 0, 0, 255]);
+        assert_eq!(&photon_image.raw_pixels[60..64], &[255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_local_thresholds_handle_uneven_lighting() {
+        // A horizontal lighting gradient with a darker line of "text" across the middle row
+        let (width, height) = (40, 9);
+        let mut pixels = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                let paper = 60 + x * 4;
+                let v = if y == 4 { paper - 50 } else { paper } as u8;
+                pixels.extend_from_slice(&[v, v, v, 255]);
+            }
+        }
+
+        let mut mean = PhotonImage::new(pixels.clone(), width, height);
+        let map = adaptive_threshold(&mut mean, AdaptiveMethod::Mean, 5, 5.0);
+        let mut gaussian = PhotonImage::new(pixels.clone(), width, height);
+        adaptive_threshold(&mut gaussian, AdaptiveMethod::Gaussian, 5, 5.0);
+        let mut sauvola = PhotonImage::new(pixels.clone(), width, height);
+        sauvola_threshold(&mut sauvola, 5, 0.2);
+        let mut niblack = PhotonImage::new(pixels.clone(), width, height);
+        niblack_threshold(&mut niblack, 5, -0.2);
+        assert_eq!(map.raw_pixels.len(), pixels.len());
+
+        // Columns next to the left and right edges are skipped, where clipped neighbourhoods skew Niblack's threshold
+        for result in [mean, gaussian, sauvola, niblack].iter() {
+            for x in 2..width as usize - 2 {
+                assert_eq!(result.raw_pixels[(4 * width as usize + x) * 4], 0);
+                assert_eq!(result.raw_pixels[x * 4], 255);
+            }
+        }
+    }
+
+    #[test]
+    fn test_morphology_on_binary_image() {
+        // A single white pixel in the centre of a black 7x7 image
+        let mut pixels = [0, 0, 0, 255].repeat(49);
+        pixels[24 * 4..24 * 4 + 3].copy_from_slice(&[255, 255, 255]);
+        let white = |img: &PhotonImage| img.raw_pixels.chunks(4).filter(|px| px[0] == 255).count();
+
+        let shapes = [(StructuringElement::Square, 25), (StructuringElement::Cross, 9), (StructuringElement::Disk, 13)];
+        for (shape, area) in shapes.iter() {
+            let mut dilated = PhotonImage::new(pixels.clone(), 7, 7);
+            dilate(&mut dilated, *shape, 2);
+            assert_eq!(white(&dilated), *area);
+
+            // Opening removes the speck, closing and eroding the dilation brings it back
+            let mut opened = PhotonImage::new(pixels.clone(), 7, 7);
+            open(&mut opened, *shape, 1);
+            assert_eq!(white(&opened), 0);
+            erode(&mut dilated, *shape, 2);
+            assert_eq!(dilated.raw_pixels, pixels);
+
+            let mut top = PhotonImage::new(pixels.clone(), 7, 7);
+            top_hat(&mut top, *shape, 1);
+            assert_eq!(top.raw_pixels, pixels);
+        }
+
+        let mut gradient = PhotonImage::new(pixels.clone(), 7, 7);
+        morphological_gradient(&mut gradient, StructuringElement::Cross, 1);
+        assert_eq!(white(&gradient), 5);
+        assert!(gradient.raw_pixels.chunks(4).all(|px| px[3] == 255));
+
+        let mut black = PhotonImage::new(pixels.clone(), 7, 7);
+        black_hat(&mut black, StructuringElement::Square, 1);
+        assert_eq!(white(&black), 0);
+    }
+
+    #[test]
+    fn test_denoise_preserves_flat_images_and_edges() {
+        // Left half dark, right half bright
+        let mut pixels = vec![];
+        for _ in 0..8 {
+            for x in 0..8 {
+                let v = if x < 4 { 40 } else { 200 };
+                pixels.extend_from_slice(&[v, v, v, 255]);
+            }
+        }
+        let filters: Vec<Box<dyn Fn(&mut PhotonImage)>> = vec![
+            Box::new(|img| median_filter(img, 1)),
+            Box::new(|img| bilateral_filter(img, 1.5, 20.0)),
+            Box::new(|img| guided_filter(img, 2, 0.001)),
+            Box::new(|img| non_local_means(img, 10.0, 1, 3)),
+            Box::new(noise_reduction),
+        ];
+        for filter in filters.iter() {
+            let mut flat = PhotonImage::new(vec![90; 8 * 8 * 4], 8, 8);
+            filter(&mut flat);
+            assert!(flat.raw_pixels.iter().all(|v| *v == 90));
+
+            let mut edge = PhotonImage::new(pixels.clone(), 8, 8);
+            filter(&mut edge);
+            for (actual, expected) in edge.raw_pixels.iter().zip(pixels.iter()) {
+                assert!((*actual as i16 - *expected as i16).abs() <= 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_denoise_reduces_noise() {
+        // A mid-grey image with a fixed pattern of noise, including salt-and-pepper outliers
+        let noise = [0i16, 12, -9, 4, -15, 7, 0, -4, 10, -12, 255, -6, 3, 14, -255, -2];
+        let pixels: Vec<u8> = (0..16 * 16).flat_map(|i| {
+            let v = num::clamp(128 + noise[(i * 7) % 16], 0, 255) as u8;
+            vec![v, v, v, 255]
+        }).collect();
+        let deviation = |img: &PhotonImage| {
+            img.raw_pixels.chunks(4).map(|px| (px[0] as f32 - 128.0).powi(2)).sum::<f32>().sqrt()
+        };
+        let original = deviation(&PhotonImage::new(pixels.clone(), 16, 16));
+
+        let mut median = PhotonImage::new(pixels.clone(), 16, 16);
+        median_filter(&mut median, 1);
+        assert!(deviation(&median) < original / 4.0);
+
+        let mut nlm = PhotonImage::new(pixels.clone(), 16, 16);
+        non_local_means(&mut nlm, 20.0, 1, 4);
+        assert!(deviation(&nlm) < original);
+    }
+
+    #[test]
+    fn test_unsharp_mask_increases_edge_contrast() {
+        // A soft vertical edge between a dark red and a light red
+        let mut pixels = vec![];
+        for _ in 0..8 {
+            for x in 0..16 {
+                let v = 60 + x as u8 * 8;
+                pixels.extend_from_slice(&[v + 60, v / 2, v / 2, 255]);
+            }
+        }
+        let contrast = |img: &PhotonImage| img.raw_pixels[(4 * 16 + 8) * 4] as i16 - img.raw_pixels[(4 * 16 + 7) * 4] as i16;
+
+        let mut sharpened = PhotonImage::new(pixels.clone(), 16, 8);
+        unsharp_mask(&mut sharpened, 1.0, 1.0, 0, false);
+        // Ramps have no curvature, so only the ends of the ramp are changed
+        assert!(sharpened.raw_pixels[(4 * 16) * 4] < pixels[(4 * 16) * 4]);
+        assert!(sharpened.raw_pixels.chunks(4).all(|px| px[3] == 255));
+
+        // A threshold above every difference leaves the image untouched
+        let mut untouched = PhotonImage::new(pixels.clone(), 16, 8);
+        unsharp_mask(&mut untouched, 1.0, 1.0, 255, true);
+        assert_eq!(untouched.raw_pixels, pixels);
+        assert_eq!(contrast(&untouched), 8);
+
+        // Luminance-only sharpening shifts all channels of a pixel by the same amount
+        let mut luma = PhotonImage::new(pixels.clone(), 16, 8);
+        unsharp_mask(&mut luma, 1.0, 1.0, 0, true);
+        let (before, after) = (&pixels[(4 * 16) * 4..(4 * 16) * 4 + 3], &luma.raw_pixels[(4 * 16) * 4..(4 * 16) * 4 + 3]);
+        let shifts: Vec<i16> = before.iter().zip(after.iter()).map(|(b, a)| *a as i16 - *b as i16).collect();
+        assert!(shifts[0] < 0 && shifts.iter().all(|shift| (shift - shifts[0]).abs() <= 1));
+    }
+
+    #[test]
+    fn test_high_pass_and_clarity_leave_flat_images() {
+        let mut flat = PhotonImage::new(vec![200; 8 * 8 * 4], 8, 8);
+        high_pass(&mut flat, 2.0);
+        assert!(flat.raw_pixels.chunks(4).all(|px| px == [128, 128, 128, 200]));
+
+        let mut flat = PhotonImage::new(vec![90; 8 * 8 * 4], 8, 8);
+        clarity(&mut flat, 1.0);
+        assert!(flat.raw_pixels.iter().all(|v| *v == 90));
+    }
+
+    #[test]
+    fn test_vignette_darkens_and_lightens_edges() {
+        let (width, height) = (21, 11);
+        let grey = vec![128; width * height * 4];
+        let at = |img: &PhotonImage, x: usize, y: usize| img.raw_pixels[(y * width + x) * 4];
+
+        let mut dark = PhotonImage::new(grey.clone(), width as u32, height as u32);
+        vignette(&mut dark, 1.0, 0.5, 0.3, 0.0, Rgb::new(0, 0, 0));
+        assert_eq!(at(&dark, 10, 5), 128);
+        assert_eq!(at(&dark, 0, 0), 0);
+        assert!(at(&dark, 17, 5) < 128 && at(&dark, 17, 5) > 0);
+        assert!(dark.raw_pixels.chunks(4).all(|px| px[3] == 128));
+
+        let mut light = PhotonImage::new(grey.clone(), width as u32, height as u32);
+        vignette(&mut light, -0.5, 0.5, 0.3, 1.0, Rgb::new(255, 255, 255));
+        assert_eq!(at(&light, 10, 5), 128);
+        assert!(at(&light, 0, 0) > 180);
+
+        // Moving the centre moves the untouched area
+        let mut offset = PhotonImage::new(grey.clone(), width as u32, height as u32);
+        vignette_with_centre(&mut offset, 1.0, 0.3, 0.2, 0.0, Rgb::new(0, 0, 0), 0.0, 0.0);
+        assert_eq!(at(&offset, 0, 0), 128);
+        assert_eq!(at(&offset, 20, 10), 0);
+    }
+
+    #[test]
+    fn test_seeded_noise_is_reproducible() {
+        let pixels = vec![128; 32 * 32 * 4];
+        let noises: Vec<Box<dyn Fn(&mut PhotonImage, u32)>> = vec![
+            Box::new(|img, seed| add_gaussian_noise(img, 10.0, false, seed)),
+            Box::new(|img, seed| add_uniform_noise(img, 20.0, true, seed)),
+            Box::new(|img, seed| add_salt_and_pepper_noise(img, 0.1, seed)),
+            Box::new(|img, seed| film_grain(img, 10.0, 2.0, seed)),
+            Box::new(|img, seed| pink_noise_seeded(img, seed)),
+        ];
+        for noise in noises.iter() {
+            let mut first = PhotonImage::new(pixels.clone(), 32, 32);
+            let mut second = PhotonImage::new(pixels.clone(), 32, 32);
+            let mut other = PhotonImage::new(pixels.clone(), 32, 32);
+            noise(&mut first, 7);
+            noise(&mut second, 7);
+            noise(&mut other, 8);
+            assert_eq!(first.raw_pixels, second.raw_pixels);
+            assert_ne!(first.raw_pixels, other.raw_pixels);
+            assert!(first.raw_pixels.chunks(4).all(|px| px[3] == 128));
+        }
+    }
+
+    #[test]
+    fn test_noise_distributions() {
+        let n = 64 * 64;
+        let mut gaussian = PhotonImage::new(vec![128; n * 4], 64, 64);
+        add_gaussian_noise(&mut gaussian, 10.0, false, 1);
+        let values: Vec<f32> = gaussian.raw_pixels.chunks(4).flat_map(|px| px[..3].to_vec()).map(|v| v as f32).collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let deviation = (values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32).sqrt();
+        assert!((mean - 128.0).abs() < 0.5);
+        assert!((deviation - 10.0).abs() < 0.5);
+
+        let mut mono = PhotonImage::new(vec![128; n * 4], 64, 64);
+        add_uniform_noise(&mut mono, 20.0, true, 1);
+        assert!(mono.raw_pixels.chunks(4).all(|px| px[0] == px[1] && px[1] == px[2] && px[0] >= 108 && px[0] <= 148));
+
+        let mut salt = PhotonImage::new(vec![128; n * 4], 64, 64);
+        add_salt_and_pepper_noise(&mut salt, 0.2, 1);
+        let replaced = salt.raw_pixels.chunks(4).filter(|px| px[0] != 128).count() as f32 / n as f32;
+        assert!((replaced - 0.2).abs() < 0.02);
+
+        // Grain leaves pure black and white untouched
+        let mut extremes = PhotonImage::new([0, 0, 0, 255, 255, 255, 255, 255].repeat(n / 2), 64, 64);
+        film_grain(&mut extremes, 30.0, 1.0, 1);
+        assert_eq!(extremes.raw_pixels, [0, 0, 0, 255, 255, 255, 255, 255].repeat(n / 2));
+    }
+
+    #[test]
+    fn test_generate_noise_textures() {
+        let kinds = [NoiseKind::Perlin, NoiseKind::Simplex, NoiseKind::Value, NoiseKind::Worley];
+        for kind in kinds.iter() {
+            let texture = generate_noise(64, 48, *kind, 16.0, 4, 0.5, 2.0, 3);
+            assert_eq!((texture.width, texture.height, texture.raw_pixels.len()), (64, 48, 64 * 48 * 4));
+            assert!(texture.raw_pixels.chunks(4).all(|px| px[0] == px[1] && px[1] == px[2] && px[3] == 255));
+
+            // Textures are reproducible from the seed, and use a good part of the tonal range
+            assert_eq!(texture.raw_pixels, generate_noise(64, 48, *kind, 16.0, 4, 0.5, 2.0, 3).raw_pixels);
+            assert_ne!(texture.raw_pixels, generate_noise(64, 48, *kind, 16.0, 4, 0.5, 2.0, 4).raw_pixels);
+            let min = texture.raw_pixels.chunks(4).map(|px| px[0]).min().unwrap();
+            let max = texture.raw_pixels.chunks(4).map(|px| px[0]).max().unwrap();
+            assert!(max - min > 60);
+        }
+
+        // Noise is smooth: neighbouring pixels of a single octave differ only slightly
+        let perlin = perlin_noise(64, 1, 32.0, 9);
+        for pair in perlin.raw_pixels.chunks(4).collect::<Vec<_>>().windows(2) {
+            assert!((pair[0][0] as i16 - pair[1][0] as i16).abs() <= 12);
+        }
+    }
+
+    #[test]
+    fn test_distortions_identity_cases() {
+        let (width, height) = (12u32, 8u32);
+        let pixels: Vec<u8> = (0..width * height).flat_map(|i| vec![(i * 2) as u8, (i * 3) as u8, 255 - i as u8, 255]).collect();
+        let cases: Vec<Box<dyn Fn(&mut PhotonImage)>> = vec![
+            Box::new(|img| remap(img, |x, y| (x, y))),
+            Box::new(|img| swirl(img, 0.0, 1.0)),
+            Box::new(|img| pinch(img, 0.0, 1.0)),
+            Box::new(|img| wave(img, 0.0, 10.0)),
+            Box::new(|img| ripple(img, 0.0, 10.0)),
+            Box::new(|img| fisheye(img, 0.0)),
+            Box::new(|img| displacement_map(img, &PhotonImage::new(vec![128; 4 * 4 * 4], 4, 4), 10.0, 10.0)),
+        ];
+        for case in cases.iter() {
+            let mut photon_image = PhotonImage::new(pixels.clone(), width, height);
+            case(&mut photon_image);
+            assert_eq!(photon_image.raw_pixels, pixels);
+        }
+    }
+
+    #[test]
+    fn test_distortions_move_pixels() {
+        let (width, height) = (16u32, 16u32);
+        let pixels: Vec<u8> = (0..width * height).flat_map(|i| vec![(i % width * 16) as u8, (i / width * 16) as u8, 0, 255]).collect();
+        let at = |img: &PhotonImage, x: u32, y: u32| img.raw_pixels[((y * width + x) * 4) as usize..((y * width + x) * 4 + 4) as usize].to_vec();
+
+        // Pixels outside the swirl radius stay put, pixels inside move
+        let mut swirled = PhotonImage::new(pixels.clone(), width, height);
+        swirl(&mut swirled, 90.0, 0.5);
+        assert_eq!(at(&swirled, 0, 0), at(&PhotonImage::new(pixels.clone(), width, height), 0, 0));
+        assert_ne!(at(&swirled, 5, 8), vec![80, 128, 0, 255]);
+
+        // A uniform displacement shifts the whole image
+        let mut shifted = PhotonImage::new(pixels.clone(), width, height);
+        displacement_map(&mut shifted, &PhotonImage::new([192, 128, 0, 255].repeat(4), 2, 2), 4.0, 4.0);
+        assert_eq!(at(&shifted, 3, 5), vec![80, 80, 0, 255]);
+
+        // Barrel distortion keeps the corners (nearly) in place and magnifies the centre
+        let mut barrel = PhotonImage::new(pixels.clone(), width, height);
+        fisheye(&mut barrel, 1.0);
+        assert!(at(&barrel, 15, 15)[0] >= 224);
+        assert!(at(&barrel, 12, 8)[0] < 192);
+    }
+
+    #[test]
+    fn test_solarize_inverts_above_threshold() {
+        let mut photon_image = PhotonImage::new(vec![100, 200, 255, 255, 128, 129, 0, 128], 2, 1);
+        solarize(&mut photon_image, 128, ChannelMask::all());
+        assert_eq!(photon_image.raw_pixels, vec![100, 55, 0, 255, 128, 126, 0, 128]);
+
+        let mut photon_image = PhotonImage::new(vec![200, 200, 200, 255], 1, 1);
+        solarize(&mut photon_image, 128, ChannelMask::new(false, true, false));
+        assert_eq!(photon_image.raw_pixels, vec![200, 55, 200, 255]);
+    }
+
+    #[test]
+    fn test_posterize_levels() {
+        let pixels: Vec<u8> = (0..=255).flat_map(|v| vec![v as u8, v as u8, v as u8, 255]).collect();
+        let mut photon_image = PhotonImage::new(pixels, 256, 1);
+        posterize(&mut photon_image, 4);
+        let mut levels: Vec<u8> = photon_image.raw_pixels.chunks(4).map(|px| px[0]).collect();
+        levels.dedup();
+        assert_eq!(levels, vec![0, 85, 170, 255]);
+
+        let mut photon_image = PhotonImage::new(vec![10, 127, 128, 255], 1, 1);
+        posterize(&mut photon_image, 2);
+        assert_eq!(photon_image.raw_pixels, vec![0, 0, 255, 255]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_posterize_needs_two_levels() {
+        posterize(&mut PhotonImage::new(vec![0, 0, 0, 255], 1, 1), 1);
+    }
+
+    #[test]
+    fn test_channel_inversion_filters() {
+        let pixels = vec![10, 20, 255, 255];
+        let cases: Vec<(fn(&mut PhotonImage), Vec<u8>)> = vec![
+            (neue, vec![10, 20, 0, 255]),
+            (lix, vec![245, 235, 255, 255]),
+            (ryo, vec![245, 20, 0, 255]),
+        ];
+        for (filter, expected) in cases {
+            let mut photon_image = PhotonImage::new(pixels.clone(), 1, 1);
+            filter(&mut photon_image);
+            assert_eq!(photon_image.raw_pixels, expected);
+        }
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 1, 1);
+        invert_channels(&mut photon_image, ChannelMask::all());
+        assert_eq!(photon_image.raw_pixels, vec![245, 235, 0, 255]);
+    }
+
+    #[test]
+    fn test_brightness_clamps_at_channel_boundaries() {
+        let pixels = vec![0, 1, 128, 255, 254, 255, 250, 100];
+        let mut photon_image = PhotonImage::new(pixels.clone(), 2, 1);
+        brightness(&mut photon_image, 0.02);
+        assert_eq!(photon_image.raw_pixels, vec![5, 6, 133, 255, 255, 255, 255, 100]);
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 2, 1);
+        brightness(&mut photon_image, -0.02);
+        assert_eq!(photon_image.raw_pixels, vec![0, 0, 123, 255, 249, 250, 245, 100]);
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 2, 1);
+        brightness(&mut photon_image, -5.0);
+        assert_eq!(photon_image.raw_pixels, vec![0, 0, 0, 255, 0, 0, 0, 100]);
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 2, 1);
+        inc_brightness(&mut photon_image, 255);
+        assert_eq!(photon_image.raw_pixels, vec![255, 255, 255, 255, 255, 255, 255, 100]);
+    }
+
+    #[test]
+    fn test_tint_moves_towards_colour() {
+        let pixels = vec![0, 128, 255, 255, 255, 0, 0, 40];
+        let mut photon_image = PhotonImage::new(pixels.clone(), 2, 1);
+        tint(&mut photon_image, Rgb::new(255, 0, 255), 0.5);
+        assert_eq!(photon_image.raw_pixels, vec![128, 64, 255, 255, 255, 0, 128, 40]);
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 2, 1);
+        tint(&mut photon_image, Rgb::new(10, 20, 30), 2.0);
+        assert_eq!(photon_image.raw_pixels, vec![10, 20, 30, 255, 10, 20, 30, 40]);
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 2, 1);
+        tint(&mut photon_image, Rgb::new(10, 20, 30), 0.0);
+        assert_eq!(photon_image.raw_pixels, pixels);
+    }
+
+    #[test]
+    fn test_alter_channels_saturates_every_pixel() {
+        let mut photon_image = PhotonImage::new(vec![200, 10, 100, 255, 250, 5, 0, 255], 2, 1);
+        alter_channels(&mut photon_image, 120, -70, 13);
+        assert_eq!(photon_image.raw_pixels, vec![255, 0, 113, 255, 255, 0, 13, 255]);
+    }
+
+    #[test]
+    fn test_lut_identity_and_linear_grades() {
+        let pixels: Vec<u8> = (0..64u32).flat_map(|i| vec![(i * 4) as u8, (255 - i * 3) as u8, (i * 37 % 256) as u8, 200]).collect();
+
+        for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral].iter() {
+            let mut lut = Lut3D::identity(17).unwrap();
+            lut.set_interpolation(*interpolation);
+            let mut photon_image = PhotonImage::new(pixels.clone(), 8, 8);
+            apply_lut(&mut photon_image, &lut);
+            assert_eq!(photon_image.raw_pixels, pixels);
+
+            // A linear grade is reproduced exactly by both interpolations, even with a tiny lattice
+            let mut lut = Lut3D::from_fn(2, |[r, g, b]| [1.0 - r, (r + g) / 2.0, b]).unwrap();
+            lut.set_interpolation(*interpolation);
+            let mut photon_image = PhotonImage::new(vec![100, 50, 7, 255], 1, 1);
+            apply_lut(&mut photon_image, &lut);
+            assert_eq!(photon_image.raw_pixels, vec![155, 75, 7, 255]);
+        }
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 8, 8);
+        apply_lut_1d(&mut photon_image, &Lut1D::identity(5).unwrap());
+        assert_eq!(photon_image.raw_pixels, pixels);
+    }
+
+    #[test]
+    fn test_parse_cube_files() {
+        let text = "# Created by hand
+TITLE \"Invert\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0.0 0.0 0.0
+DOMAIN_MAX 1.0 1.0 1.0
+
+1.0 1.0 1.0
+0.0 1.0 1.0
+1.0 0.0 1.0
+0.0 0.0 1.0
+1.0 1.0 0.0
+0.0 1.0 0.0
+1.0 0.0 0.0
+0.0 0.0 0.0
+";
+        let lut = Lut3D::from_cube_bytes(text.as_bytes().to_vec()).unwrap();
+        assert_eq!(lut.get_size(), 2);
+        let mut photon_image = PhotonImage::new(vec![0, 100, 255, 255, 30, 30, 30, 0], 2, 1);
+        apply_lut(&mut photon_image, &lut);
+        assert_eq!(photon_image.raw_pixels, vec![255, 155, 0, 255, 225, 225, 225, 0]);
+
+        // A 1D LUT over a doubled input range, which halves every channel
+        let text = "LUT_1D_SIZE 3\nLUT_1D_INPUT_RANGE 0.0 2.0\n0 0 0\n0.5 0.5 0.5\n1 1 1\n";
+        let lut = Lut1D::from_cube(text).unwrap();
+        let mut photon_image = PhotonImage::new(vec![0, 100, 255, 255], 1, 1);
+        apply_lut_1d(&mut photon_image, &lut);
+        assert_eq!(photon_image.raw_pixels, vec![0, 50, 128, 255]);
+    }
+
+    #[test]
+    fn test_parse_cube_errors() {
+        assert_eq!(Lut3D::from_cube("LUT_3D_SIZE 2\n0 0 0\n1 1 1\n"), Err(LutError::WrongEntryCount { expected: 8, found: 2 }));
+        assert_eq!(Lut1D::from_cube("LUT_1D_SIZE 2\n0 0 0\n1 x 1\n"),
+                   Err(LutError::InvalidNumber { line: 3, word: "x".to_string() }));
+        assert_eq!(Lut1D::from_cube("LUT_1D_SIZE 2\n0 0\n1 1 1\n"), Err(LutError::WrongValueCount { line: 2, expected: 3, found: 2 }));
+        assert_eq!(Lut1D::from_cube("LUT_1D_SIZE 2\nLUT_SCALE 1 1\n"),
+                   Err(LutError::UnknownKeyword { line: 2, keyword: "LUT_SCALE".to_string() }));
+        assert_eq!(Lut3D::from_cube("LUT_3D_SIZE 4294967296\n"), Err(LutError::SizeTooLarge(4294967296)));
+        assert_eq!(Lut3D::from_cube("LUT_3D_SIZE 1\n"), Err(LutError::InvalidSize { line: 1, size: "1".to_string() }));
+        assert_eq!(Lut3D::from_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n"), Err(LutError::MissingSize(3)));
+        assert_eq!(Lut1D::from_cube("LUT_1D_SIZE 2\nDOMAIN_MAX 0 1 1\n0 0 0\n1 1 1\n"), Err(LutError::InvalidDomain));
+        assert!(Lut1D::from_cube_bytes(vec![0xff, 0xfe, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_lut_constructor_errors() {
+        assert_eq!(Lut1D::new(vec![0.0], vec![0.0, 1.0], vec![0.0, 1.0]), Err(LutError::CurveTooShort));
+        assert_eq!(Lut1D::identity(1), Err(LutError::SizeTooSmall(1)));
+        assert_eq!(Lut3D::identity(0), Err(LutError::SizeTooSmall(0)));
+        assert_eq!(Lut3D::identity(1 << 40), Err(LutError::SizeTooLarge(1 << 40)));
+        assert_eq!(Lut3D::new(2, vec![0.0; 23]), Err(LutError::WrongDataLength { size: 2, expected: 24, found: 23 }));
+
+        let line = vec![0.0, 0.0, 1.0, 1.0];
+        assert!(Lut1D::from_control_points(line.clone(), line.clone(), line.clone()).is_ok());
+        for points in [vec![0.0, 0.0], vec![0.0, 0.0, 1.0], vec![0.5, 0.0, 0.5, 1.0], vec![0.0, std::f32::NAN, 1.0, 1.0]].iter() {
+            assert_eq!(Lut1D::from_control_points(points.clone(), line.clone(), line.clone()), Err(LutError::InvalidControlPoints));
+        }
+    }
+
+    #[test]
+    fn test_filter_registry_presets() {
+        let registry = FilterRegistry::new();
+        let names: Vec<String> = registry.list_filters().iter().map(|info| info.get_name()).collect();
+        assert_eq!(names.len(), 24);
+        assert!(names.contains(&"vintage".to_string()) && names.contains(&"ryo".to_string()));
+        assert!(registry.list_filters().iter().all(|info| info.get_builtin() && !info.get_description().is_empty()));
+
+        let pixels: Vec<u8> = (0..16u32).flat_map(|i| vec![(i * 16) as u8, 100, (255 - i * 16) as u8, 255]).collect();
+        let mut expected = PhotonImage::new(pixels.clone(), 4, 4);
+        crate::monochrome::monochrome(&mut expected, 5, 40, 20);
+        let mut photon_image = PhotonImage::new(pixels.clone(), 4, 4);
+        assert_eq!(filter(&mut photon_image, "radio"), Ok(()));
+        assert_eq!(photon_image.raw_pixels, expected.raw_pixels);
+
+        let mut photon_image = PhotonImage::new(pixels.clone(), 4, 4);
+        assert_eq!(filter(&mut photon_image, "no_such_filter"), Err(FilterError::UnknownFilter("no_such_filter".to_string())));
+        assert_eq!(photon_image.raw_pixels, pixels);
+    }
+
+    #[test]
+    fn test_register_custom_filters() {
+        let mut registry = FilterRegistry::new();
+        let mut pipeline = FilterPipeline::new();
+        pipeline.invert_channels(ChannelMask::all());
+        pipeline.posterize(2);
+        assert_eq!(pipeline.get_length(), 2);
+
+        assert_eq!(registry.register_filter("punch", "Inverted and posterized.", &pipeline), Ok(()));
+        assert_eq!(registry.register_filter("mauve", "", &pipeline), Err(FilterError::BuiltinFilter("mauve".to_string())));
+        assert_eq!(registry.register_filter("", "", &pipeline), Err(FilterError::EmptyName));
+        let custom = registry.list_filters().pop().unwrap();
+        assert_eq!((custom.get_name().as_str(), custom.get_builtin()), ("punch", false));
+
+        let mut photon_image = PhotonImage::new(vec![10, 200, 130, 255], 1, 1);
+        assert_eq!(registry.apply_filter(&mut photon_image, "punch"), Ok(()));
+        assert_eq!(photon_image.raw_pixels, vec![255, 0, 0, 255]);
+
+        assert_eq!(registry.unregister_filter("punch"), Ok(()));
+        assert!(!registry.has_filter("punch"));
+        assert!(registry.apply_filter(&mut photon_image, "punch").is_err());
+    }
+
+    /// A colourful test image with gradients, greys and a semi-transparent row, for golden-image tests.
+    fn golden_input() -> PhotonImage {
+        let (width, height) = (24u32, 16u32);
+        let mut raw_pixels = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                let alpha = if y == height - 1 { 128 } else { 255 };
+                if x < 4 {
+                    let v = (y * 17) as u8;
+                    raw_pixels.extend_from_slice(&[v, v, v, alpha]);
+                } else {
+                    raw_pixels.extend_from_slice(&[(x * 11) as u8, (y * 16) as u8, (255 - x * 5 - y * 6) as u8, alpha]);
+                }
+            }
+        }
+        PhotonImage::new(raw_pixels, width, height)
+    }
+
+    #[test]
+    fn test_composite_presets_match_golden_images() {
+        // Run with PHOTON_UPDATE_GOLDEN=1 to regenerate the golden images after an intentional change
+        let update = std::env::var("PHOTON_UPDATE_GOLDEN").is_ok();
+        let registry = FilterRegistry::new();
+        for name in ["clarendon", "gingham", "juno", "lark", "valencia", "nashville"].iter() {
+            let mut photon_image = golden_input();
+            registry.apply_filter(&mut photon_image, name).unwrap();
+            let path = format!("{}/tests/golden/{}.png", env!("CARGO_MANIFEST_DIR"), name);
+
+            if update {
+                let buffer = image::RgbaImage::from_vec(photon_image.width, photon_image.height, photon_image.raw_pixels).unwrap();
+                buffer.save(&path).unwrap();
+                continue;
+            }
+            let golden = image::open(&path).unwrap().to_rgba().into_raw();
+            assert_eq!(golden.len(), photon_image.raw_pixels.len());
+            for (i, (actual, expected)) in photon_image.raw_pixels.iter().zip(golden.iter()).enumerate() {
+                // Allow for rounding differences in floating-point maths between platforms
+                assert!((*actual as i32 - *expected as i32).abs() <= 1, "{} differs from its golden image at byte {}", name, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_preset_intensity_blends_with_original() {
+        let original = golden_input();
+        let mut none = golden_input();
+        clarendon(&mut none, 0.0);
+        assert_eq!(none.raw_pixels, original.raw_pixels);
+
+        let mut full = golden_input();
+        clarendon(&mut full, 1.0);
+        let mut half = golden_input();
+        clarendon(&mut half, 0.5);
+        for i in 0..original.raw_pixels.len() {
+            let (a, b) = (original.raw_pixels[i].min(full.raw_pixels[i]), original.raw_pixels[i].max(full.raw_pixels[i]));
+            assert!(half.raw_pixels[i] >= a && half.raw_pixels[i] <= b);
+        }
+        assert_ne!(half.raw_pixels, full.raw_pixels);
+    }
+
+    #[test]
+    fn test_apply_in_rect_only_touches_rect() {
+        let pixels: Vec<u8> = (0..6 * 4u32).flat_map(|i| vec![(i * 10) as u8, 50, 200, 255]).collect();
+        let mut photon_image = PhotonImage::new(pixels.clone(), 6, 4);
+        apply_in_rect(&mut photon_image, 2, 1, 3, 2, |img| {
+            assert_eq!((img.get_width(), img.get_height()), (3, 2));
+            invert_channels(img, ChannelMask::all());
+        });
+        for (i, (px, original)) in photon_image.raw_pixels.chunks(4).zip(pixels.chunks(4)).enumerate() {
+            let (x, y) = (i % 6, i / 6);
+            if (2..5).contains(&x) && (1..3).contains(&y) {
+                assert_eq!(px, &[255 - original[0], 205, 55, 255][..]);
+            } else {
+                assert_eq!(px, original);
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_with_mask_blends_by_mask_value() {
+        let mut photon_image = PhotonImage::new(vec![100, 100, 100, 255].repeat(3), 3, 1);
+        let mask = PhotonImage::new(vec![255, 255, 255, 255, 128, 128, 128, 255, 255, 255, 255, 0], 3, 1);
+        apply_with_mask(&mut photon_image, &mask, |img| brightness(img, 0.4));
+        assert_eq!(photon_image.raw_pixels, vec![202, 202, 202, 255, 151, 151, 151, 255, 100, 100, 100, 255]);
+
+        let mut pipeline = FilterPipeline::new();
+        pipeline.posterize(2);
+        let mut photon_image = PhotonImage::new(vec![100, 100, 100, 255].repeat(3), 3, 1);
+        pipeline.apply_with_mask(&mut photon_image, &mask);
+        assert_eq!(&photon_image.raw_pixels[..8], &[0, 0, 0, 255, 50, 50, 50, 255]);
+    }
+
+    #[test]
+    fn test_gradient_masks() {
+        let linear = linear_gradient_mask(10, 1, 0.0, 0.0, 10.0, 0.0);
+        let values: Vec<u8> = linear.raw_pixels.chunks(4).map(|px| px[0]).collect();
+        assert_eq!(values, vec![242, 217, 191, 166, 140, 115, 89, 64, 38, 13]);
+
+        let radial = radial_gradient_mask(9, 9, 4.5, 4.5, 2.0, 2.0);
+        let at = |x: usize, y: usize| radial.raw_pixels[(y * 9 + x) * 4];
+        assert_eq!(at(4, 4), 255);
+        assert_eq!(at(0, 0), 0);
+        assert!(at(7, 4) > 0 && at(7, 4) < 255);
+    }
+
+    #[test]
+    fn test_blend_pixels_modes() {
+        assert_eq!(blend_pixels([100, 100, 100, 255], [255, 128, 0, 255], BlendMode::Multiply), [100, 50, 0, 255]);
+        assert_eq!(blend_pixels([100, 100, 100, 255], [0, 0, 0, 255], BlendMode::Screen), [100, 100, 100, 255]);
+        assert_eq!(blend_pixels([100, 100, 100, 255], [30, 200, 100, 255], BlendMode::Normal), [30, 200, 100, 255]);
+        assert_eq!(BlendMode::from_name("exclusion"), Some(BlendMode::Exclusion));
+    }
+
+    #[test]
+    fn test_document_flatten_layers() {
+        let background = PhotonImage::new(vec![100, 100, 100, 255].repeat(4), 2, 2);
+        let mut document = Document::from_image(&background).unwrap();
+        assert_eq!(document.flatten().raw_pixels, background.raw_pixels);
+
+        // A half-transparent layer offset so only one pixel overlaps the document
+        let mut top = Layer::image("Top", &PhotonImage::new(vec![200, 0, 0, 255].repeat(4), 2, 2), 1, 1);
+        top.set_opacity(0.5);
+        document.add_layer(&top).unwrap();
+        let flattened = document.flatten();
+        assert_eq!(&flattened.raw_pixels[..12], &background.raw_pixels[..12]);
+        assert_eq!(&flattened.raw_pixels[12..], &[150, 50, 50, 255]);
+
+        document.layer_mut(1).set_visible(false);
+        assert_eq!(document.flatten().raw_pixels, background.raw_pixels);
+
+        document.move_layer(1, 0);
+        assert_eq!(document.get_layer(0).get_name(), "Top");
+        document.remove_layer(0);
+        assert_eq!(document.layer_count(), 1);
+    }
+
+    #[test]
+    fn test_document_adjustment_layer_with_mask() {
+        let background = PhotonImage::new(vec![100, 100, 100, 255].repeat(2), 2, 1);
+        let mut document = Document::from_image(&background).unwrap();
+        let mut pipeline = FilterPipeline::new();
+        pipeline.posterize(2);
+        let mut adjustment = Layer::adjustment("Posterize", &pipeline);
+        adjustment.set_mask(&PhotonImage::new(vec![255, 255, 255, 255, 0, 0, 0, 255], 2, 1));
+        document.add_layer(&adjustment).unwrap();
+        assert_eq!(document.flatten().raw_pixels, vec![0, 0, 0, 255, 100, 100, 100, 255]);
+        // Adjustments don't change the layers beneath them
+        assert!(!document.get_layer(0).is_adjustment());
+        assert_eq!(document.layer_count(), 2);
+    }
+
+    #[test]
+    fn test_document_json_round_trip() {
+        let mut document = Document::new(3, 2).unwrap();
+        let mut layer = Layer::image("Layer", &PhotonImage::new((0..24).collect(), 3, 2), 0, 0);
+        layer.set_blend_mode(BlendMode::Screen);
+        document.add_layer(&layer).unwrap();
+        let mut pipeline = FilterPipeline::new();
+        pipeline.brightness(0.2);
+        document.add_layer(&Layer::adjustment("Brighten", &pipeline)).unwrap();
+
+        let loaded = Document::from_json(&document.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.layer_count(), 2);
+        assert_eq!(loaded.get_layer(0).get_blend_mode(), BlendMode::Screen);
+        assert_eq!(loaded.flatten().raw_pixels, document.flatten().raw_pixels);
+    }
+
+    fn pattern_image(width: u32, height: u32) -> PhotonImage {
+        let raw_pixels = (0..width * height * 4).map(|i| ((i * 37 + i / 7 * 11) % 256) as u8).collect();
+        PhotonImage::new(raw_pixels, width, height)
+    }
+
+    #[test]
+    fn test_apply_tiled_matches_whole_image() {
+        let mut expected = pattern_image(23, 17);
+        gaussian_blur(&mut expected);
+        let mut tiled = pattern_image(23, 17);
+        apply_tiled(&mut tiled, 7, 1, gaussian_blur);
+        assert_eq!(tiled.raw_pixels, expected.raw_pixels);
+
+        // A halo wider than the tiles still gives each tile all of its neighbours
+        let mut tiled = pattern_image(23, 17);
+        apply_tiled(&mut tiled, 1, 2, gaussian_blur);
+        assert_eq!(tiled.raw_pixels, expected.raw_pixels);
+
+        let mut expected = pattern_image(23, 17);
+        posterize(&mut expected, 3);
+        let mut tiled = pattern_image(23, 17);
+        apply_tiled(&mut tiled, 5, 0, |tile| posterize(tile, 3));
+        assert_eq!(tiled.raw_pixels, expected.raw_pixels);
+    }
+
+    #[test]
+    fn test_process_stream_png_matches_whole_image() {
+        let original = pattern_image(19, 13);
+        let mut input = vec![];
+        image::png::PNGEncoder::new(&mut input).encode(&original.raw_pixels, 19, 13, image::ColorType::RGBA(8)).unwrap();
+
+        let mut output = vec![];
+        {
+            let mut source = PngRowSource::new(&input[..]).unwrap();
+            assert_eq!(source.dimensions(), (19, 13));
+            let mut sink = PngRowSink::new(&mut output, 19, 13).unwrap();
+            process_stream(&mut source, &mut sink, 4, 1, sharpen).unwrap();
+            sink.finish().unwrap();
+        }
+
+        let mut expected = original;
+        sharpen(&mut expected);
+        let decoded = image::load_from_memory(&output).unwrap().to_rgba().into_raw();
+        assert_eq!(decoded, expected.raw_pixels);
+    }
+
+    #[test]
+    fn test_png_row_source_opens_huge_png() {
+        // A 100 MP header, over the png crate's default limit of 2^26 pixels
+        let mut header = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut header, 10000, 10000);
+            encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+            encoder.write_header().unwrap().write_chunk(png::chunk::IDAT, &[]).unwrap();
+        }
+        let source = PngRowSource::new(&header[..]).unwrap();
+        assert_eq!(source.dimensions(), (10000, 10000));
+    }
+
+    #[test]
+    fn test_process_png_bytes_matches_pipeline() {
+        let original = pattern_image(11, 9);
+        let mut input = vec![];
+        image::png::PNGEncoder::new(&mut input).encode(&original.raw_pixels, 11, 9, image::ColorType::RGBA(8)).unwrap();
+
+        let mut pipeline = FilterPipeline::new();
+        pipeline.monochrome(40, 20, 0);
+        pipeline.posterize(4);
+        let output = process_png_bytes(&input, &pipeline, 2).unwrap();
+
+        let mut expected = original;
+        pipeline.apply(&mut expected);
+        let decoded = image::load_from_memory(&output).unwrap().to_rgba().into_raw();
+        assert_eq!(decoded, expected.raw_pixels);
+    }
+
+    #[test]
+    fn test_set_pixels_updates_in_place() {
+        let mut photon_image = PhotonImage::new(vec![0; 8], 2, 1);
+        let ptr = photon_image.get_pixels_ptr();
+        photon_image.set_pixels(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(photon_image.get_pixels_ptr(), ptr);
+        assert_eq!(photon_image.get_pixels_len(), 8);
+        assert_eq!(photon_image.raw_pixels, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_guided_filter_keeps_flat_areas() {
+        let mut photon_image = PhotonImage::new(vec![90, 140, 200, 255].repeat(36), 6, 6);
+        guided_filter(&mut photon_image, 2, 1e-6);
+        assert_eq!(photon_image.raw_pixels, vec![90, 140, 200, 255].repeat(36));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_guided_filter_rejects_zero_epsilon() {
+        let mut photon_image = PhotonImage::new(vec![90, 140, 200, 255].repeat(36), 6, 6);
+        guided_filter(&mut photon_image, 2, 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit inside")]
+    fn test_blend_in_rect_rejects_overflowing_rect() {
+        let mut photon_image = PhotonImage::new(vec![0; 16], 2, 2);
+        let edited = photon_image.clone();
+        blend_in_rect(&mut photon_image, &edited, u32::MAX, 0, 2, 1);
+    }
+
+    #[test]
+    fn test_blend_output_is_unchanged() {
+        let base = PhotonImage::new(vec![100, 100, 100, 255, 200, 50, 20, 255], 2, 1);
+        let top = PhotonImage::new(vec![255, 128, 0, 255, 30, 220, 90, 255], 2, 1);
+        // blend keeps the output existing callers rely on, including "exclusion" blending like "dodge"
+        let expected = [
+            ("multiply", vec![168, 123, 0, 255, 86, 114, 46, 255]),
+            ("exclusion", vec![255, 229, 168, 255, 242, 255, 98, 255]),
+            ("dodge", vec![255, 229, 168, 255, 242, 255, 98, 255]),
+            ("over", vec![255, 188, 0, 255, 96, 239, 160, 255]),
+        ];
+        for (mode, pixels) in expected.iter() {
+            let mut photon_image = base.clone();
+            blend(&mut photon_image, &top, mode);
+            assert_eq!(&photon_image.raw_pixels, pixels, "{}", mode);
+        }
+    }
+
+    #[test]
+    fn test_document_from_json_errors() {
+        assert!(matches!(Document::from_json("{"), Err(DocumentError::Json(_))));
+
+        // Layers edited in place aren't checked until the document is loaded
+        let mut document = Document::new(2, 1).unwrap();
+        document.add_layer(&Layer::image("Broken", &PhotonImage::new(vec![0; 8], 2, 1), 0, 0)).unwrap();
+        *document.layer_mut(0).content_mut() = LayerContent::Image(PhotonImage::new(vec![0; 4], 2, 1));
+        assert_eq!(Document::from_json(&document.to_json().unwrap()).unwrap_err(), DocumentError::InvalidImage("Broken".to_string()));
+
+        let mut document = Document::new(2, 1).unwrap();
+        document.add_layer(&Layer::image("Masked", &PhotonImage::new(vec![0; 8], 2, 1), 0, 0)).unwrap();
+        document.layer_mut(0).set_mask(&PhotonImage::new(vec![0; 4], 1, 1));
+        assert_eq!(Document::from_json(&document.to_json().unwrap()).unwrap_err(), DocumentError::InvalidMask("Masked".to_string()));
+    }
+
+    #[test]
+    fn test_document_rejects_invalid_layers() {
+        assert_eq!(Document::new(u32::MAX, u32::MAX).unwrap_err(), DocumentError::InvalidSize);
+
+        let mut document = Document::new(2, 1).unwrap();
+        let broken = Layer::image("Broken", &PhotonImage::new(vec![0; 4], 2, 1), 0, 0);
+        assert_eq!(document.add_layer(&broken).unwrap_err(), DocumentError::InvalidImage("Broken".to_string()));
+
+        let mut masked = Layer::adjustment("Masked", &FilterPipeline::new());
+        masked.set_mask(&PhotonImage::new(vec![255; 4], 1, 1));
+        let expected = DocumentError::InvalidMask("Masked".to_string());
+        assert_eq!(document.add_layer(&masked).unwrap_err(), expected);
+        assert_eq!(document.insert_layer(0, &masked).unwrap_err(), expected);
+        document.add_layer(&Layer::adjustment("Plain", &FilterPipeline::new())).unwrap();
+        assert_eq!(document.set_layer(0, &masked).unwrap_err(), expected);
+        assert_eq!(document.layer_count(), 1);
+        assert_eq!(document.get_layer(0).get_name(), "Plain");
+    }
+
+    #[test]
+    fn test_apply_with_intensity_zero_or_nan_leaves_image() {
+        let original = PhotonImage::new(vec![100, 150, 200, 255, 10, 20, 30, 255], 2, 1);
+        let mut pipeline = FilterPipeline::new();
+        pipeline.posterize(2);
+        for intensity in [0.0, -1.0, std::f32::NAN].iter() {
+            let mut photon_image = original.clone();
+            pipeline.apply_with_intensity(&mut photon_image, *intensity);
+            assert_eq!(photon_image.raw_pixels, original.raw_pixels);
+        }
+    }
+
+    #[test]
+    fn test_replace_background_takes_background_alpha() {
+        let mut photon_image = PhotonImage::new(vec![0, 255, 0, 255, 200, 30, 30, 255], 2, 1);
+        let background = PhotonImage::new(vec![10, 20, 30, 0, 40, 50, 60, 128], 2, 1);
+        replace_background(&mut photon_image, &background, Rgb::new(0, 255, 0), DeltaE::Cie76, 20.0, 0.0);
+        assert_eq!(photon_image.raw_pixels, vec![10, 20, 30, 0, 200, 30, 30, 255]);
+    }
+
+    #[test]
+    fn test_dither_to_empty_palette_leaves_image() {
+        let mut photon_image = PhotonImage::new(vec![10, 120, 230, 255, 90, 60, 30, 128], 2, 1);
+        dither_to_palette(&mut photon_image, DitherAlgorithm::FloydSteinberg, vec![]);
+        assert_eq!(photon_image.raw_pixels, vec![10, 120, 230, 255, 90, 60, 30, 128]);
+    }
+}
@@ -0,0 +1,255 @@
+//! Procedural noise textures, such as Perlin, simplex, value and Worley noise.
+//!
+//! Textures are generated as greyscale PhotonImages, which can be used as backgrounds, overlays
+//! blended with `multiple::blend`, or displacement maps.
+
+use wasm_bindgen::prelude::*;
+use crate::noise::Prng;
+use crate::PhotonImage;
+
+/// Procedural noise algorithms.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseKind {
+    Perlin = 1,
+    Simplex = 2,
+    Value = 3,
+    Worley = 4,
+}
+
+/// Hash a lattice point to a pseudo-random 32-bit value.
+fn hash(seed: u32, x: i32, y: i32) -> u32 {
+    let mut h = seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (y as u32).wrapping_mul(0x1656_67b1);
+    h = (h ^ (h >> 15)).wrapping_mul(0x85eb_ca6b);
+    h = (h ^ (h >> 13)).wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+/// Turn a hash into a float from 0 to 1.
+fn unit(h: u32) -> f32 {
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// The dot product of the offset `(x, y)` with one of eight gradient directions chosen by the hash.
+fn gradient(h: u32, x: f32, y: f32) -> f32 {
+    match h & 7 {
+        0 => x + y,
+        1 => x - y,
+        2 => -x + y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Improved Perlin noise, from roughly -1 to 1.
+fn perlin(seed: u32, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let (u, v) = (fade(fx), fade(fy));
+
+    let n00 = gradient(hash(seed, x0, y0), fx, fy);
+    let n10 = gradient(hash(seed, x0 + 1, y0), fx - 1.0, fy);
+    let n01 = gradient(hash(seed, x0, y0 + 1), fx, fy - 1.0);
+    let n11 = gradient(hash(seed, x0 + 1, y0 + 1), fx - 1.0, fy - 1.0);
+    lerp(lerp(n00, n10, u), lerp(n01, n11, u), v)
+}
+
+/// Simplex noise, from roughly -1 to 1. Cheaper than Perlin noise, and without its axis-aligned artifacts.
+fn simplex(seed: u32, x: f32, y: f32) -> f32 {
+    let skew = 0.5 * (3.0f32.sqrt() - 1.0);
+    let unskew = (3.0 - 3.0f32.sqrt()) / 6.0;
+
+    // Find the simplex (triangle) containing the point
+    let s = (x + y) * skew;
+    let (i, j) = ((x + s).floor() as i32, (y + s).floor() as i32);
+    let t = (i + j) as f32 * unskew;
+    let (x0, y0) = (x - (i as f32 - t), y - (j as f32 - t));
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+    let corners = [
+        (0, 0, x0, y0),
+        (i1, j1, x0 - i1 as f32 + unskew, y0 - j1 as f32 + unskew),
+        (1, 1, x0 - 1.0 + 2.0 * unskew, y0 - 1.0 + 2.0 * unskew),
+    ];
+
+    let mut total = 0.0;
+    for (di, dj, cx, cy) in corners.iter() {
+        let falloff = 0.5 - cx * cx - cy * cy;
+        if falloff > 0.0 {
+            total += falloff.powi(4) * gradient(hash(seed, i + di, j + dj), *cx, *cy);
+        }
+    }
+    // Scale the result to roughly -1 to 1
+    70.0 * total
+}
+
+/// Value noise, from -1 to 1: random values at lattice points, smoothly interpolated.
+fn value(seed: u32, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (u, v) = (fade(x - x0 as f32), fade(y - y0 as f32));
+    let corner = |dx: i32, dy: i32| unit(hash(seed, x0 + dx, y0 + dy)) * 2.0 - 1.0;
+    lerp(lerp(corner(0, 0), corner(1, 0), u), lerp(corner(0, 1), corner(1, 1), u), v)
+}
+
+/// Worley (cellular) noise, from -1 to 1: the distance to the nearest of a set of randomly placed feature points,
+/// with one point in each lattice cell.
+fn worley(seed: u32, x: f32, y: f32) -> f32 {
+    let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+    let mut nearest = f32::MAX;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let h = hash(seed, cx + dx, cy + dy);
+            let px = (cx + dx) as f32 + unit(h);
+            let py = (cy + dy) as f32 + unit(hash(h, 0, 1));
+            nearest = nearest.min((px - x).powi(2) + (py - y).powi(2));
+        }
+    }
+    nearest.sqrt().min(1.0) * 2.0 - 1.0
+}
+
+/// Generate a greyscale noise texture.
+///
+/// Several octaves of noise are layered as fractal Brownian motion: each octave has its frequency
+/// multiplied by `lacunarity` and its amplitude multiplied by `persistence`, adding finer and finer detail.
+///
+/// # Arguments
+/// * `width` - The width of the texture, in pixels.
+/// * `height` - The height of the texture, in pixels.
+/// * `kind` - The noise algorithm. Perlin = 1, Simplex = 2, Value = 3, Worley = 4.
+/// * `scale` - The size of the largest features, in pixels.
+/// * `octaves` - The number of layers of noise. 1 gives plain noise; 4 to 8 gives natural-looking detail.
+/// * `persistence` - How much each octave's amplitude is multiplied by. 0.5 is typical.
+/// * `lacunarity` - How much each octave's frequency is multiplied by. 2.0 is typical.
+/// * `seed` - The seed for the noise. The same seed always gives the same texture.
+///
+/// # Example
+///
+/// ```
+/// // For example, to generate a cloudy 512x512 texture:
+/// use photon::textures::{generate_noise, NoiseKind};
+/// let clouds = generate_noise(512, 512, NoiseKind::Perlin, 128.0, 6, 0.5, 2.0, 42);
+/// ```
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn generate_noise(width: u32, height: u32, kind: NoiseKind, scale: f32, octaves: u32, persistence: f32,
+                      lacunarity: f32, seed: u32) -> PhotonImage {
+    if scale <= 0.0 {
+        panic!("The noise scale must be greater than 0");
+    }
+    let noise: fn(u32, f32, f32) -> f32 = match kind {
+        NoiseKind::Perlin => perlin,
+        NoiseKind::Simplex => simplex,
+        NoiseKind::Value => value,
+        NoiseKind::Worley => worley,
+    };
+
+    // Each octave gets its own seed, so that octaves don't line up at the origin
+    let mut rng = Prng::new(seed);
+    let octave_seeds: Vec<u32> = (0..octaves.max(1)).map(|_| rng.next_u64() as u32).collect();
+    let total_amplitude: f32 = (0..octave_seeds.len()).map(|i| persistence.powi(i as i32)).sum();
+
+    let mut raw_pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut frequency = 1.0 / scale;
+            let mut amplitude = 1.0;
+            let mut total = 0.0;
+            for octave_seed in octave_seeds.iter() {
+                total += noise(*octave_seed, x as f32 * frequency, y as f32 * frequency) * amplitude;
+                frequency *= lacunarity;
+                amplitude *= persistence;
+            }
+
+            let v = num::clamp(((total / total_amplitude + 1.0) * 127.5).round(), 0.0, 255.0) as u8;
+            raw_pixels.extend_from_slice(&[v, v, v, 255]);
+        }
+    }
+    PhotonImage::new(raw_pixels, width, height)
+}
+
+/// Generate a single octave of Perlin noise.
+///
+/// # Arguments
+/// * `width` - The width of the texture, in pixels.
+/// * `height` - The height of the texture, in pixels.
+/// * `scale` - The size of the features, in pixels.
+/// * `seed` - The seed for the noise.
+///
+/// # Example
+///
+/// ```
+/// use photon::textures::perlin_noise;
+/// let texture = perlin_noise(256, 256, 32.0, 42);
+/// ```
+#[wasm_bindgen]
+pub fn perlin_noise(width: u32, height: u32, scale: f32, seed: u32) -> PhotonImage {
+    generate_noise(width, height, NoiseKind::Perlin, scale, 1, 0.5, 2.0, seed)
+}
+
+/// Generate a single octave of simplex noise.
+///
+/// # Arguments
+/// * `width` - The width of the texture, in pixels.
+/// * `height` - The height of the texture, in pixels.
+/// * `scale` - The size of the features, in pixels.
+/// * `seed` - The seed for the noise.
+///
+/// # Example
+///
+/// ```
+/// use photon::textures::simplex_noise;
+/// let texture = simplex_noise(256, 256, 32.0, 42);
+/// ```
+#[wasm_bindgen]
+pub fn simplex_noise(width: u32, height: u32, scale: f32, seed: u32) -> PhotonImage {
+    generate_noise(width, height, NoiseKind::Simplex, scale, 1, 0.5, 2.0, seed)
+}
+
+/// Generate a single octave of value noise, which is blockier than Perlin noise.
+///
+/// # Arguments
+/// * `width` - The width of the texture, in pixels.
+/// * `height` - The height of the texture, in pixels.
+/// * `scale` - The size of the features, in pixels.
+/// * `seed` - The seed for the noise.
+///
+/// # Example
+///
+/// ```
+/// use photon::textures::value_noise;
+/// let texture = value_noise(256, 256, 32.0, 42);
+/// ```
+#[wasm_bindgen]
+pub fn value_noise(width: u32, height: u32, scale: f32, seed: u32) -> PhotonImage {
+    generate_noise(width, height, NoiseKind::Value, scale, 1, 0.5, 2.0, seed)
+}
+
+/// Generate a single octave of Worley (cellular) noise, which looks like cells, stone or scales.
+///
+/// # Arguments
+/// * `width` - The width of the texture, in pixels.
+/// * `height` - The height of the texture, in pixels.
+/// * `scale` - The average distance between cell centres, in pixels.
+/// * `seed` - The seed for the noise.
+///
+/// # Example
+///
+/// ```
+/// use photon::textures::worley_noise;
+/// let texture = worley_noise(256, 256, 32.0, 42);
+/// ```
+#[wasm_bindgen]
+pub fn worley_noise(width: u32, height: u32, scale: f32, seed: u32) -> PhotonImage {
+    generate_noise(width, height, NoiseKind::Worley, scale, 1, 0.5, 2.0, seed)
+}
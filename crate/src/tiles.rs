@@ -0,0 +1,347 @@
+//! Process images in tiles or strips, so that very large images never need to be copied whole.
+//!
+//! Point operations, which change each pixel on its own, and neighbourhood operations with a fixed radius, such as
+//! the 3x3 convolutions in `conv`, give the same result tile by tile as on the whole image, as long as each tile
+//! is given a halo of surrounding pixels at least as wide as the radius. Operations that depend on the size of the
+//! image, such as vignettes, or that move pixels arbitrarily far, such as resizing, can't be tiled.
+//!
+//! `apply_tiled` works on an image in memory, so operations only ever copy one tile at a time.
+//! `process_stream` only ever holds one strip of rows, reading them from a `RowSource` and writing them to a `RowSink`.
+//! `PngRowSource` and `PngRowSink` stream PNG files a row at a time, and `process_png_bytes` uses them to apply a
+//! `FilterPipeline` to a PNG on the web.
+
+use std::cmp::min;
+use std::io::{self, Read, Write};
+use deflate::write::ZlibEncoder;
+use png::HasParameters;
+use wasm_bindgen::prelude::*;
+use crate::filters::{FilterPipeline, FilterStep};
+use crate::PhotonImage;
+
+/// The size of the compressed chunks written by `PngRowSink`.
+const IDAT_SIZE: usize = 64 * 1024;
+
+/// A source of image rows, read from the top of the image down.
+pub trait RowSource {
+    /// The width and height of the image.
+    fn dimensions(&self) -> (u32, u32);
+
+    /// Read the next rows of the image as RGBA pixels, filling `buf`, whose length is a whole number of rows.
+    fn read_rows(&mut self, buf: &mut [u8]) -> io::Result<()>;
+}
+
+/// A destination for image rows, written from the top of the image down.
+pub trait RowSink {
+    /// Write the next rows of the image, as RGBA pixels.
+    fn write_rows(&mut self, rows: &[u8]) -> io::Result<()>;
+}
+
+/// Apply an operation to an image one tile at a time.
+///
+/// Each tile is given to the operation with a halo of up to `halo` pixels of the original image on each side,
+/// clipped to the edges of the image, and only the tile itself is copied back.
+///
+/// # Arguments
+/// * `photon_image` - A PhotonImage.
+/// * `tile_size` - The width and height of each tile, not including the halo.
+/// * `halo` - The width of the border of neighbouring pixels given to the operation. Use 0 for point operations.
+/// * `operation` - The operation to apply. It mustn't change the size of the image.
+///
+/// # Example
+///
+/// ```
+/// // For example, to sharpen an image in 512x512 tiles, with the 1 pixel halo a 3x3 kernel needs:
+/// use photon::tiles::apply_tiled;
+/// use photon::conv::sharpen;
+/// apply_tiled(&mut img, 512, 1, sharpen);
+/// ```
+pub fn apply_tiled<F>(photon_image: &mut PhotonImage, tile_size: u32, halo: u32, mut operation: F)
+    where F: FnMut(&mut PhotonImage) {
+    if tile_size == 0 {
+        panic!("Tiles must be at least 1 pixel in size");
+    }
+    let (width, height) = (photon_image.width, photon_image.height);
+    let row_bytes = (width * 4) as usize;
+
+    // The original rows above the current band of tiles, which the previous band has already overwritten
+    let mut above: Vec<u8> = vec![];
+    let mut y = 0;
+    while y < height {
+        let band_end = min(y + tile_size, height);
+        let top = y.saturating_sub(halo);
+        let bottom = min(band_end + halo, height);
+        let mut band = above;
+        band.extend_from_slice(&photon_image.raw_pixels[y as usize * row_bytes..bottom as usize * row_bytes]);
+
+        let next_top = band_end.saturating_sub(halo);
+        above = band[(next_top - top) as usize * row_bytes..(band_end - top) as usize * row_bytes].to_vec();
+
+        let mut x = 0;
+        while x < width {
+            let tile_end = min(x + tile_size, width);
+            let left = x.saturating_sub(halo);
+            let right = min(tile_end + halo, width);
+            let tile_width = right - left;
+
+            let mut pixels = Vec::with_capacity((tile_width * (bottom - top) * 4) as usize);
+            for row in band.chunks(row_bytes) {
+                pixels.extend_from_slice(&row[(left * 4) as usize..(right * 4) as usize]);
+            }
+            let mut tile = PhotonImage::new(pixels, tile_width, bottom - top);
+            operation(&mut tile);
+            if tile.width != tile_width || tile.height != bottom - top {
+                panic!("Operations applied in tiles mustn't change the size of the image");
+            }
+
+            for row in y..band_end {
+                let start = (((row - top) * tile_width + x - left) * 4) as usize;
+                let target = ((row * width + x) * 4) as usize;
+                let len = ((tile_end - x) * 4) as usize;
+                photon_image.raw_pixels[target..target + len].copy_from_slice(&tile.raw_pixels[start..start + len]);
+            }
+            x = tile_end;
+        }
+        y = band_end;
+    }
+}
+
+/// Apply an operation to an image as it streams from a source to a sink, one strip of rows at a time.
+///
+/// Each strip is given to the operation with up to `halo` rows of the original image above and below it,
+/// clipped to the edges of the image, and only the strip itself is written to the sink.
+/// At most `strip_height + 2 * halo` rows are held in memory at once.
+///
+/// # Arguments
+/// * `source` - Where to read the image from.
+/// * `sink` - Where to write the processed image to.
+/// * `strip_height` - The number of rows in each strip, not including the halo.
+/// * `halo` - The number of neighbouring rows given to the operation. Use 0 for point operations.
+/// * `operation` - The operation to apply. It mustn't change the size of the image.
+///
+/// # Example
+///
+/// ```
+/// // For example, to blur a PNG file too large to open, 256 rows at a time:
+/// use std::fs::File;
+/// use std::io::{BufReader, BufWriter};
+/// use photon::tiles::{process_stream, PngRowSource, PngRowSink};
+/// use photon::conv::gaussian_blur;
+/// let mut source = PngRowSource::new(BufReader::new(File::open("scan.png")?))?;
+/// let (width, height) = source.dimensions();
+/// let mut sink = PngRowSink::new(BufWriter::new(File::create("blurred.png")?), width, height)?;
+/// process_stream(&mut source, &mut sink, 256, 1, gaussian_blur)?;
+/// sink.finish()?;
+/// ```
+pub fn process_stream<S, K, F>(source: &mut S, sink: &mut K, strip_height: u32, halo: u32, mut operation: F) -> io::Result<()>
+    where S: RowSource, K: RowSink, F: FnMut(&mut PhotonImage) {
+    if strip_height == 0 {
+        panic!("Strips must be at least 1 row in height");
+    }
+    let (width, height) = source.dimensions();
+    let row_bytes = (width * 4) as usize;
+
+    // The original rows from `window_start` up to `rows_read`
+    let mut window: Vec<u8> = vec![];
+    let mut window_start = 0;
+    let mut rows_read = 0;
+    let mut y = 0;
+    while y < height {
+        let strip_end = min(y + strip_height, height);
+        let needed = min(strip_end + halo, height);
+        let old_len = window.len();
+        window.resize(old_len + (needed - rows_read) as usize * row_bytes, 0);
+        source.read_rows(&mut window[old_len..])?;
+        rows_read = needed;
+
+        let mut strip = PhotonImage::new(window.clone(), width, rows_read - window_start);
+        operation(&mut strip);
+        if strip.width != width || strip.height != rows_read - window_start {
+            panic!("Operations applied in strips mustn't change the size of the image");
+        }
+        let start = (y - window_start) as usize * row_bytes;
+        sink.write_rows(&strip.raw_pixels[start..start + (strip_end - y) as usize * row_bytes])?;
+
+        // Keep only the rows the next strip needs as its upper halo
+        let next_start = strip_end.saturating_sub(halo).max(window_start);
+        window.drain(..(next_start - window_start) as usize * row_bytes);
+        window_start = next_start;
+        y = strip_end;
+    }
+    Ok(())
+}
+
+/// Reads a non-interlaced PNG a row at a time, converting it to RGBA.
+pub struct PngRowSource<R: Read> {
+    reader: png::Reader<R>,
+    color_type: png::ColorType,
+    width: u32,
+    height: u32,
+}
+
+impl<R: Read> PngRowSource<R> {
+    /// Read the header of a PNG. Interlaced PNGs can't be read a row at a time, so they return an error.
+    pub fn new(r: R) -> io::Result<PngRowSource<R>> {
+        // The decoder's default limit of 2^26 pixels guards against allocating a whole frame,
+        // but rows are read one at a time, so any size that fits a row in memory can be streamed
+        let limits = png::Limits { pixels: u64::MAX };
+        let (info, mut reader) = png::Decoder::new_with_limits(r, limits).read_info()?;
+        if reader.info().interlaced {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Interlaced PNGs can't be read a row at a time"));
+        }
+        if info.width > (u32::MAX - 1) / 4 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "The PNG is too wide to be read a row at a time"));
+        }
+        // Palettes, low bit depths and 16-bit channels are expanded or stripped to 8 bits per channel
+        let (color_type, _) = reader.output_color_type();
+        Ok(PngRowSource { reader, color_type, width: info.width, height: info.height })
+    }
+}
+
+impl<R: Read> RowSource for PngRowSource<R> {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn read_rows(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let color_type = self.color_type;
+        for target in buf.chunks_mut((self.width * 4) as usize) {
+            let row = match self.reader.next_row()? {
+                Some(row) => row,
+                None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The PNG has no more rows")),
+            };
+            for (i, px) in target.chunks_mut(4).enumerate() {
+                let rgba = match color_type {
+                    png::ColorType::RGBA => [row[i * 4], row[i * 4 + 1], row[i * 4 + 2], row[i * 4 + 3]],
+                    png::ColorType::RGB => [row[i * 3], row[i * 3 + 1], row[i * 3 + 2], 255],
+                    png::ColorType::GrayscaleAlpha => [row[i * 2], row[i * 2], row[i * 2], row[i * 2 + 1]],
+                    png::ColorType::Grayscale => [row[i], row[i], row[i], 255],
+                    png::ColorType::Indexed => unreachable!("Palettes are expanded while decoding"),
+                };
+                px.copy_from_slice(&rgba);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes an RGBA PNG a row at a time. Call `finish` once every row has been written.
+pub struct PngRowSink<W: Write> {
+    encoder: ZlibEncoder<IdatWriter<W>>,
+    width: u32,
+    height: u32,
+    rows_written: u32,
+    filtered: Vec<u8>,
+}
+
+/// Splits the compressed image data into IDAT chunks as it's written.
+struct IdatWriter<W: Write> {
+    writer: png::Writer<W>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> Write for IdatWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= IDAT_SIZE {
+            self.writer.write_chunk(png::chunk::IDAT, &self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write> PngRowSink<W> {
+    /// Write the header of a PNG with the given dimensions.
+    pub fn new(w: W, width: u32, height: u32) -> io::Result<PngRowSink<W>> {
+        let mut encoder = png::Encoder::new(w, width, height);
+        encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+        let idat = IdatWriter { writer: encoder.write_header()?, buffer: vec![] };
+        Ok(PngRowSink {
+            encoder: ZlibEncoder::new(idat, deflate::Compression::Default),
+            width,
+            height,
+            rows_written: 0,
+            filtered: vec![0; (width * 4 + 1) as usize],
+        })
+    }
+
+    /// Write the end of the PNG. Returns an error if fewer rows were written than the height of the image.
+    pub fn finish(self) -> io::Result<()> {
+        if self.rows_written != self.height {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("Only {} of the PNG's {} rows were written", self.rows_written, self.height)));
+        }
+        let mut idat = self.encoder.finish()?;
+        if !idat.buffer.is_empty() {
+            idat.writer.write_chunk(png::chunk::IDAT, &idat.buffer)?;
+        }
+        // The end of the PNG is written when the writer is dropped
+        Ok(())
+    }
+}
+
+impl<W: Write> RowSink for PngRowSink<W> {
+    fn write_rows(&mut self, rows: &[u8]) -> io::Result<()> {
+        for row in rows.chunks((self.width * 4) as usize) {
+            if self.rows_written == self.height {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "More rows were written than the height of the PNG"));
+            }
+            // Each row uses the Sub filter, storing the difference from the pixel to its left
+            self.filtered[0] = 1;
+            for (i, value) in row.iter().enumerate() {
+                let left = if i >= 4 { row[i - 4] } else { 0 };
+                self.filtered[i + 1] = value.wrapping_sub(left);
+            }
+            self.encoder.write_all(&self.filtered)?;
+            self.rows_written += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Apply a filter pipeline to the bytes of a PNG, decoding, filtering and encoding a strip of rows at a time,
+/// so the whole image is never decoded into memory. Returns the bytes of the filtered PNG.
+///
+/// Every step but the vignette changes each pixel on its own, so no halo is needed. Vignettes depend on the size
+/// of the image, so pipelines containing one return an error, as do interlaced or invalid PNGs.
+/// # Arguments
+/// * `png` - The bytes of a non-interlaced PNG.
+/// * `pipeline` - The filter pipeline to apply.
+/// * `strip_height` - The number of rows processed at a time. Must be at least 1.
+///
+/// # Example
+///
+/// ```
+/// // For example, to apply a pipeline to a PNG 256 rows at a time:
+/// use photon::filters::FilterPipeline;
+/// use photon::tiles::process_png_bytes;
+/// let mut pipeline = FilterPipeline::new();
+/// pipeline.monochrome(40, 20, 0);
+/// let filtered = process_png_bytes(&png_bytes, &pipeline, 256)?;
+/// ```
+#[wasm_bindgen]
+pub fn process_png_bytes(png: &[u8], pipeline: &FilterPipeline, strip_height: u32) -> Result<Vec<u8>, JsValue> {
+    if pipeline.steps().iter().any(|step| matches!(step, FilterStep::Vignette(..))) {
+        return Err(JsValue::from_str("Vignettes depend on the size of the image, so can't be applied in strips"));
+    }
+    if strip_height == 0 {
+        return Err(JsValue::from_str("Strips must be at least 1 row in height"));
+    }
+    filter_png(png, pipeline, strip_height).map_err(|error| JsValue::from_str(&error.to_string()))
+}
+
+fn filter_png(png: &[u8], pipeline: &FilterPipeline, strip_height: u32) -> io::Result<Vec<u8>> {
+    let mut source = PngRowSource::new(png)?;
+    let (width, height) = source.dimensions();
+    let mut output = vec![];
+    {
+        let mut sink = PngRowSink::new(&mut output, width, height)?;
+        process_stream(&mut source, &mut sink, strip_height, 0, |img| pipeline.apply(img))?;
+        sink.finish()?;
+    }
+    Ok(output)
+}
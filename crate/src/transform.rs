@@ -11,6 +11,8 @@ use image::RgbaImage;
 // use std::f64::consts::PI;
 // use std::f64;
 use web_sys::{ImageData, HtmlCanvasElement};
+#[cfg(target_arch = "wasm32")]
+use web_sys::OffscreenCanvas;
 use wasm_bindgen::Clamped;
 
 /// Crop an image.
@@ -171,6 +173,29 @@ pub fn resize(photon_img: &PhotonImage, width: u32, height: u32, sampling_filter
     return canvas;
 }
 
+/// Resize an image on the web, onto an OffscreenCanvas. Unlike `resize`, this works in Web Workers.
+///
+/// # Arguments
+/// * `img` - A PhotonImage.
+/// * `width` - New width.
+/// * `height` - New height.
+/// * `sampling_filter` - Nearest = 1, Triangle = 2, CatmullRom = 3, Gaussian = 4, Lanczos3 = 5
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn resize_offscreen(photon_img: &PhotonImage, width: u32, height: u32, sampling_filter: SamplingFilter) -> Result<OffscreenCanvas, JsValue> {
+    let sampling_filter = filter_type_from_sampling_filter(sampling_filter);
+    let dyn_img = helpers::dyn_image_from_raw(&photon_img);
+    let resized_img = image::ImageRgba8(image::imageops::resize(&dyn_img, width, height, sampling_filter));
+
+    let (canvas, ctx) = crate::offscreen_canvas(resized_img.width(), resized_img.height())?;
+    let new_img_data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&mut resized_img.raw_pixels()), canvas.width(), canvas.height())?;
+
+    // Place the new imagedata onto the canvas
+    ctx.put_image_data(&new_img_data, 0.0, 0.0)?;
+
+    Ok(canvas)
+}
+
 /// Resize an image.
 /// 
 /// # Arguments
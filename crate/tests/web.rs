@@ -0,0 +1,51 @@
+//! Tests of the OffscreenCanvas and ImageBitmap functions, which need a browser.
+//! Run them with `wasm-pack test --headless --chrome` or `--firefox`.
+#![cfg(target_arch = "wasm32")]
+
+use photon_rs::{open_image_bitmap, open_image_offscreen, put_image_data_offscreen, to_image_bitmap, PhotonImage};
+use photon_rs::transform::{resize_offscreen, SamplingFilter};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn gradient(width: u32, height: u32) -> PhotonImage {
+    let mut pixels = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            pixels.extend_from_slice(&[(x * 40) as u8, (y * 40) as u8, 100, 255]);
+        }
+    }
+    PhotonImage::new(pixels, width, height)
+}
+
+#[wasm_bindgen_test]
+fn offscreen_round_trip() {
+    let mut image = gradient(5, 3);
+    let canvas = OffscreenCanvas::new(5, 3).unwrap();
+    let ctx = canvas.get_context("2d").unwrap().unwrap()
+        .dyn_into::<OffscreenCanvasRenderingContext2d>().unwrap();
+    put_image_data_offscreen(&ctx, &mut image).unwrap();
+
+    let opened = open_image_offscreen(&canvas, &ctx).unwrap();
+    assert_eq!((opened.get_width(), opened.get_height()), (5, 3));
+    assert_eq!(opened.get_raw_pixels(), image.get_raw_pixels());
+}
+
+#[wasm_bindgen_test]
+fn image_bitmap_round_trip() {
+    let mut image = gradient(4, 6);
+    let bitmap = to_image_bitmap(&mut image).unwrap();
+    assert_eq!((bitmap.width(), bitmap.height()), (4, 6));
+
+    let opened = open_image_bitmap(&bitmap).unwrap();
+    assert_eq!(opened.get_raw_pixels(), image.get_raw_pixels());
+}
+
+#[wasm_bindgen_test]
+fn resize_offscreen_has_new_size() {
+    let image = gradient(8, 8);
+    let canvas = resize_offscreen(&image, 3, 5, SamplingFilter::Nearest).unwrap();
+    assert_eq!((canvas.width(), canvas.height()), (3, 5));
+}
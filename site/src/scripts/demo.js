@@ -114,10 +114,8 @@ var newimg, watermark_img, img2;
    }
   
    function updateCanvas(new_image) {
-     let new_pixels = module.to_image_data(new_image);
-    
-    // Place the pixels back on the canvas
-     ctx.putImageData(new_pixels, 0, 0);
+     // Draw straight from wasm memory, without copying the pixels into new ImageData first
+     ctx.putImageData(new_image.get_image_data_view(), 0, 0);
    }
 
    function vec_to_photonimage_example() {
@@ -232,7 +230,7 @@ var newimg, watermark_img, img2;
                        "offset_blue": function(){return module.offset(rust_image, 1, 25)},
                        "offset_green": function(){return module.offset(rust_image, 2, 25)},
                        "primary" : function() {return module.primary(rust_image)},
-                       "solarize" : function() {return module.solarize(rust_image)},
+                       "solarize" : function() {return module.solarize(rust_image, 128, module.ChannelMask.all())},
                        "threshold" : function() {return module.threshold(rust_image, 100)},
                        "sepia" : function() {return module.sepia(rust_image)},
                        "decompose_min" : function(){return module.decompose_min(rust_image)},
@@ -96,7 +96,7 @@ function drawChart() {
        "offset_blue": function(){return module.offset(rust_image, 1, 15)},
        "offset_green": function(){return module.offset(rust_image, 2, 15)},
        "primary" : function() {return module.primary(rust_image)},
-       "solarize" : function() {return module.solarize(rust_image)},
+       "solarize" : function() {return module.solarize(rust_image, 128, module.ChannelMask.all())},
        "threshold" : function() {return module.threshold(rust_image, 100)},
        "sepia" : function() {return module.sepia(rust_image)},
        "decompose_min" : function(){return module.decompose_min(rust_image)},
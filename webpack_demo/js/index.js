@@ -136,7 +136,7 @@ import("../../crate/pkg").then(module => {
                       "offset_blue": function(){return module.offset(rust_image, 1, 15)},
                       "offset_green": function(){return module.offset(rust_image, 2, 15)},
                       "primary" : function() {return module.primary(rust_image)},
-                      "solarize" : function() {return module.solarize(rust_image)},
+                      "solarize" : function() {return module.solarize(rust_image, 128, module.ChannelMask.all())},
                       "threshold" : function() {return module.threshold(rust_image, 200)},
                       "sepia" : function() {return module.sepia(rust_image)},
                       "decompose_min" : function(){return module.decompose_min(rust_image)},
@@ -293,10 +293,8 @@ import("../../crate/pkg").then(module => {
   }
   
   function updateCanvas(new_image) {
-    let new_pixels = module.to_image_data(new_image);
-    
-    // Place the pixels back on the canvas
-    ctx.putImageData(new_pixels, 0, 0);
+    // Draw straight from wasm memory, without copying the pixels into new ImageData first
+    ctx.putImageData(new_image.get_image_data_view(), 0, 0);
   }
 
   function filterImage(event) {